@@ -0,0 +1,139 @@
+//! A virtual ZyTemp CO₂ sensor, created using the Linux `uhid` driver.
+//!
+//! The device has the same USB IDs as the real sensor, accepts the
+//! encryption key in the feature report and sends encrypted (or, with
+//! `--plain`, unencrypted) reports, so `co2mon` can be tested end-to-end
+//! without hardware:
+//!
+//! ```text
+//! sudo cargo run -p co2mon-sim
+//! cargo run --example watch --no-default-features --features linux-static-hidraw
+//! ```
+//!
+//! Note that only the `hidraw` backends of `hidapi` can see `uhid` devices.
+
+use std::env;
+use std::process;
+use std::time::Duration;
+
+#[cfg(target_os = "linux")]
+mod uhid;
+
+struct Options {
+    plain: bool,
+    interval: Duration,
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut options = Options {
+        plain: false,
+        interval: Duration::from_millis(500),
+    };
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--plain" => options.plain = true,
+            "--interval-ms" => {
+                let ms = args
+                    .next()
+                    .and_then(|ms| ms.parse().ok())
+                    .ok_or("--interval-ms needs a number of milliseconds")?;
+                options.interval = Duration::from_millis(ms);
+            }
+            _ => return Err(format!("unknown argument `{}`", arg)),
+        }
+    }
+    Ok(options)
+}
+
+/// Reverses the transformation done by `co2mon` when decrypting a report.
+fn encrypt(mut data: [u8; 8], key: [u8; 8]) -> [u8; 8] {
+    for (r, m) in data.iter_mut().zip(b"Htemp99e".iter()) {
+        *r = r.wrapping_add(m.rotate_right(4));
+    }
+
+    let data = u64::from_be_bytes(data).rotate_left(3);
+    let mut data = data.to_be_bytes();
+
+    for (r, k) in data.iter_mut().zip(key.iter()) {
+        *r ^= k;
+    }
+
+    data.swap(0, 2);
+    data.swap(1, 4);
+    data.swap(3, 7);
+    data.swap(5, 6);
+    data
+}
+
+/// Returns the report to send at step `tick`, cycling through the values
+/// reported by the real sensor.
+fn report(tick: u32, key: Option<[u8; 8]>) -> [u8; 8] {
+    use zg_co2::SingleReading;
+
+    let reading = match tick % 3 {
+        0 => SingleReading::CO2(600 + (tick % 800 * 7 % 800) as u16),
+        1 => SingleReading::Temperature(21.5 + (tick % 16) as f32 * 0.0625),
+        _ => SingleReading::Humidity(0.0),
+    };
+    let message = zg_co2::encode(&reading);
+    let mut data = [0; 8];
+    data[..5].copy_from_slice(&message);
+    match key {
+        Some(key) => encrypt(data, key),
+        None => data,
+    }
+}
+
+fn main() {
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}", e);
+            eprintln!("usage: co2mon-sim [--plain] [--interval-ms <ms>]");
+            process::exit(2);
+        }
+    };
+
+    #[cfg(target_os = "linux")]
+    if let Err(e) = uhid::run(&options) {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = options;
+        eprintln!("co2mon-sim is only supported on Linux");
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use co2mon::SingleReading;
+
+    #[test]
+    fn test_encrypt() {
+        let data = [0x50, 0x04, 0x57, 0xab, 0x0d, 0x00, 0x00, 0x00];
+        let encrypted = super::encrypt(data, [0; 8]);
+        assert_eq!(encrypted, [0x6c, 0xa4, 0xa2, 0xb6, 0x5d, 0x9a, 0x9c, 0x08]);
+
+        let key = [0x62, 0xea, 0x1d, 0x4f, 0x14, 0xfa, 0xe5, 0x6c];
+        let encrypted = super::encrypt(data, key);
+        let decoded = co2mon::decode_report(encrypted, key).unwrap();
+        assert_eq!(decoded, SingleReading::CO2(1111));
+    }
+
+    #[test]
+    fn test_report() {
+        let key = [1, 2, 3, 4, 5, 6, 7, 8];
+        for tick in 0..48 {
+            let reading = co2mon::decode_report(super::report(tick, Some(key)), key).unwrap();
+            assert_eq!(
+                co2mon::decode_report(super::report(tick, None), key).unwrap(),
+                reading
+            );
+        }
+    }
+}
@@ -0,0 +1,138 @@
+//! A minimal client for the `/dev/uhid` character device.
+//!
+//! See `include/uapi/linux/uhid.h` in the kernel sources for the event
+//! layout.
+
+use crate::Options;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+const UHID_START: u32 = 2;
+const UHID_STOP: u32 = 3;
+const UHID_OPEN: u32 = 4;
+const UHID_CLOSE: u32 = 5;
+const UHID_GET_REPORT: u32 = 9;
+const UHID_GET_REPORT_REPLY: u32 = 10;
+const UHID_CREATE2: u32 = 11;
+const UHID_INPUT2: u32 = 12;
+const UHID_SET_REPORT: u32 = 13;
+const UHID_SET_REPORT_REPLY: u32 = 14;
+
+/// `sizeof(struct uhid_event)`, the largest member being `uhid_create2_req`
+const EVENT_SIZE: usize = 4 + 4372;
+
+const BUS_USB: u16 = 0x03;
+const VID: u32 = 0x04d9;
+const PID: u32 = 0xa052;
+const EIO: u16 = 5;
+
+/// A vendor-defined device with 8-byte input and feature reports.
+const REPORT_DESCRIPTOR: &[u8] = &[
+    0x06, 0x00, 0xff, // Usage Page (Vendor Defined 0xFF00)
+    0x09, 0x01, // Usage (0x01)
+    0xa1, 0x01, // Collection (Application)
+    0x15, 0x00, //   Logical Minimum (0)
+    0x26, 0xff, 0x00, //   Logical Maximum (255)
+    0x75, 0x08, //   Report Size (8)
+    0x95, 0x08, //   Report Count (8)
+    0x09, 0x01, //   Usage (0x01)
+    0x81, 0x02, //   Input (Data, Variable, Absolute)
+    0x09, 0x01, //   Usage (0x01)
+    0xb1, 0x02, //   Feature (Data, Variable, Absolute)
+    0xc0, // End Collection
+];
+
+fn event(kind: u32) -> Vec<u8> {
+    let mut event = vec![0; EVENT_SIZE];
+    event[..4].copy_from_slice(&kind.to_ne_bytes());
+    event
+}
+
+fn create(mut uhid: &File) -> io::Result<()> {
+    let mut event = event(UHID_CREATE2);
+    let name = b"co2mon-sim virtual CO2 sensor";
+    event[4..4 + name.len()].copy_from_slice(name);
+    event[196..200].copy_from_slice(b"1.40");
+    event[260..262].copy_from_slice(&(REPORT_DESCRIPTOR.len() as u16).to_ne_bytes());
+    event[262..264].copy_from_slice(&BUS_USB.to_ne_bytes());
+    event[264..268].copy_from_slice(&VID.to_ne_bytes());
+    event[268..272].copy_from_slice(&PID.to_ne_bytes());
+    event[280..280 + REPORT_DESCRIPTOR.len()].copy_from_slice(REPORT_DESCRIPTOR);
+    uhid.write_all(&event)
+}
+
+fn handle_events(mut uhid: &File, key: &Mutex<Option<[u8; 8]>>) -> io::Result<()> {
+    let mut buf = vec![0; EVENT_SIZE];
+    loop {
+        let len = uhid.read(&mut buf)?;
+        if len < 4 {
+            continue;
+        }
+        let id = &buf[4..8];
+        match u32::from_ne_bytes([buf[0], buf[1], buf[2], buf[3]]) {
+            UHID_START => eprintln!("device started"),
+            UHID_STOP => eprintln!("device stopped"),
+            UHID_OPEN => eprintln!("device opened"),
+            UHID_CLOSE => eprintln!("device closed"),
+            UHID_SET_REPORT => {
+                let size = usize::from(u16::from_ne_bytes([buf[10], buf[11]]));
+                let data = &buf[12..12 + size.min(buf.len() - 12)];
+                // skip the Report ID, if present
+                let data = if data.len() == 9 { &data[1..] } else { data };
+                let err = if let Ok(new_key) = <[u8; 8]>::try_from(data) {
+                    eprintln!("received key {:02x?}", new_key);
+                    let mut key = key.lock().unwrap();
+                    if key.is_some() {
+                        *key = Some(new_key);
+                    }
+                    0
+                } else {
+                    EIO
+                };
+                let mut reply = event(UHID_SET_REPORT_REPLY);
+                reply[4..8].copy_from_slice(id);
+                reply[8..10].copy_from_slice(&err.to_ne_bytes());
+                uhid.write_all(&reply)?;
+            }
+            UHID_GET_REPORT => {
+                let mut reply = event(UHID_GET_REPORT_REPLY);
+                reply[4..8].copy_from_slice(id);
+                reply[8..10].copy_from_slice(&EIO.to_ne_bytes());
+                uhid.write_all(&reply)?;
+            }
+            _ => {}
+        }
+    }
+}
+
+pub(crate) fn run(options: &Options) -> io::Result<()> {
+    let uhid = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/uhid")?;
+    create(&uhid)?;
+    eprintln!("created virtual sensor {:04x}:{:04x}", VID, PID);
+
+    // `None` means that the reports are not encrypted
+    let key = Arc::new(Mutex::new(if options.plain { None } else { Some([0; 8]) }));
+    let events = uhid.try_clone()?;
+    let events_key = key.clone();
+    thread::spawn(move || {
+        if let Err(e) = handle_events(&events, &events_key) {
+            eprintln!("{}", e);
+        }
+    });
+
+    let mut uhid = &uhid;
+    for tick in 0.. {
+        let report = crate::report(tick, *key.lock().unwrap());
+        let mut event = event(UHID_INPUT2);
+        event[4..6].copy_from_slice(&(report.len() as u16).to_ne_bytes());
+        event[6..6 + report.len()].copy_from_slice(&report);
+        uhid.write_all(&event)?;
+        thread::sleep(options.interval);
+    }
+    Ok(())
+}
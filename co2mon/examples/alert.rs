@@ -0,0 +1,45 @@
+//! Prints a message when the CO₂ concentration goes above a threshold
+//! (`1000` ppm by default), and when it falls back below it:
+//!
+//! ```text
+//! cargo run --example alert -- 1200
+//! ```
+//!
+//! To avoid flapping, the alert is only cleared once the concentration drops
+//! 50 ppm below the threshold.
+
+use co2mon::{Result, Sensor};
+use std::env;
+use std::thread;
+use std::time::Duration;
+
+const HYSTERESIS: u16 = 50;
+
+fn main() -> Result<()> {
+    let threshold = env::args()
+        .nth(1)
+        .and_then(|threshold| threshold.parse().ok())
+        .unwrap_or(1000);
+
+    let sensor = Sensor::open_default()?;
+    let mut alert = false;
+    loop {
+        match sensor.read() {
+            Ok(reading) => {
+                let co2 = reading.co2();
+                if !alert && co2 > threshold {
+                    alert = true;
+                    println!(
+                        "\x07CO₂ above {} ppm: {} ppm, open a window",
+                        threshold, co2
+                    );
+                } else if alert && co2 < threshold.saturating_sub(HYSTERESIS) {
+                    alert = false;
+                    println!("CO₂ back to {} ppm", co2);
+                }
+            }
+            Err(e) => eprintln!("{}", e),
+        }
+        thread::sleep(Duration::from_secs(5));
+    }
+}
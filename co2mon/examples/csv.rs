@@ -0,0 +1,51 @@
+//! Logs the readings in CSV format, to a file (appending to it) or to the
+//! standard output:
+//!
+//! ```text
+//! cargo run --example csv -- co2mon.csv
+//! ```
+
+use co2mon::{Result, Sensor};
+use std::env;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+fn main() -> Result<()> {
+    let mut output: Box<dyn Write> = match env::args().nth(1) {
+        Some(path) => {
+            let file = OpenOptions::new().create(true).append(true).open(path)?;
+            if file.metadata()?.len() == 0 {
+                writeln!(&file, "timestamp,temperature,co2")?;
+            }
+            Box::new(file)
+        }
+        None => {
+            println!("timestamp,temperature,co2");
+            Box::new(io::stdout())
+        }
+    };
+
+    let sensor = Sensor::open_default()?;
+    loop {
+        match sensor.read() {
+            Ok(reading) => {
+                let timestamp = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs();
+                writeln!(
+                    output,
+                    "{},{:.4},{}",
+                    timestamp,
+                    reading.temperature(),
+                    reading.co2()
+                )?;
+                output.flush()?;
+            }
+            Err(e) => eprintln!("{}", e),
+        }
+        thread::sleep(Duration::from_secs(10));
+    }
+}
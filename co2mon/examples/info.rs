@@ -0,0 +1,141 @@
+//! Prints the information that is useful in a bug report: the USB details of
+//! every connected sensor, whether it encrypts its reports, and the opcodes,
+//! kinds of readings and errors seen during a short sample.
+//!
+//! The sensors are opened using the `CO2MON_*` environment variables, see
+//! `OpenOptions::from_env`.
+//!
+//! ```text
+//! cargo run --example info
+//! ```
+
+use co2mon::{Error, OpenOptions, Sensor, SingleReading};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+const SAMPLE: Duration = Duration::from_secs(5);
+
+fn main() -> co2mon::Result<()> {
+    let devices = Sensor::enumerate()?;
+    if devices.is_empty() {
+        println!("no sensor found");
+    }
+    for info in devices {
+        println!("path:         {}", info.path().to_string_lossy());
+        println!(
+            "USB ID:       {:04x}:{:04x}",
+            info.vendor_id(),
+            info.product_id()
+        );
+        println!("release:      {:04x}", info.release_number());
+        println!(
+            "manufacturer: {}",
+            info.manufacturer_string().unwrap_or("-")
+        );
+        println!("product:      {}", info.product_string().unwrap_or("-"));
+        println!("serial:       {}", info.serial_number().unwrap_or("-"));
+        match info.usb_location() {
+            Some((bus, ports)) => println!("USB location: bus {}, ports {:?}", bus, ports),
+            None => println!("USB location: -"),
+        }
+        println!(
+            "port path:    {}",
+            info.port_path().as_deref().unwrap_or("-")
+        );
+        println!();
+    }
+
+    for sensor in OpenOptions::from_env()?.open_all()? {
+        let path = sensor.path().unwrap_or_default();
+        println!("sensor:       {}", path.to_string_lossy());
+        println!(
+            "product ID:   {}",
+            sensor
+                .product_id()
+                .map_or_else(|| "-".into(), |pid| format!("{:04x}", pid))
+        );
+        println!(
+            "device:       {} {} {}",
+            sensor.manufacturer_string().as_deref().unwrap_or("-"),
+            sensor.product_string().as_deref().unwrap_or("-"),
+            sensor.serial_number().as_deref().unwrap_or("-")
+        );
+
+        sample_reports(&sensor)?;
+
+        let capabilities = sensor.capabilities(SAMPLE)?;
+        println!(
+            "readings:     CO₂: {}, temperature: {}, humidity: {}",
+            capabilities.co2(),
+            capabilities.temperature(),
+            capabilities.humidity()
+        );
+
+        let stats = sensor.stats();
+        println!("reports:      {}", stats.frames());
+        println!("checksum:     {} errors", stats.checksum_errors());
+        println!("invalid:      {}", stats.invalid_messages());
+        println!("timeouts:     {}", stats.timeouts());
+        println!();
+    }
+    Ok(())
+}
+
+/// Reads raw reports for a while, printing whether they are encrypted and how
+/// many of them have each opcode.
+fn sample_reports(sensor: &Sensor) -> co2mon::Result<()> {
+    let mut plaintext = 0;
+    let mut encrypted = 0;
+    let mut opcodes = BTreeMap::new();
+    let mut invalid = 0;
+    let start = Instant::now();
+    while start.elapsed() < SAMPLE {
+        let report = match sensor.read_raw(Duration::from_secs(1)) {
+            Ok(Some(report)) => report,
+            Ok(None) | Err(Error::InvalidMessage) => continue,
+            Err(e) => return Err(e),
+        };
+        // the plain reports validate as they are, the encrypted ones only once
+        // decrypted with the key
+        let plain = zg_co2::decode([report[0], report[1], report[2], report[3], report[4]]);
+        let reading = match plain {
+            Ok(reading) => {
+                plaintext += 1;
+                reading
+            }
+            Err(_) => match co2mon::decode_report(report, sensor.key()) {
+                Ok(reading) => {
+                    encrypted += 1;
+                    reading
+                }
+                Err(_) => {
+                    invalid += 1;
+                    continue;
+                }
+            },
+        };
+        let opcode = zg_co2::encode(&reading)[0];
+        let entry = opcodes.entry(opcode).or_insert((0, reading.clone()));
+        entry.0 += 1;
+        entry.1 = reading;
+    }
+
+    let variant = match (plaintext, encrypted) {
+        (0, 0) => "unknown (no valid reports)",
+        (_, 0) => "plaintext",
+        (0, _) => "encrypted",
+        _ => "mixed",
+    };
+    println!("encryption:   {}", variant);
+    println!("undecodable:  {}", invalid);
+    for (opcode, (count, reading)) in opcodes {
+        let description = match reading {
+            SingleReading::CO2(val) => format!("{} ppm CO₂", val),
+            SingleReading::Temperature(val) => format!("{:.2} °C", val),
+            SingleReading::Humidity(val) => format!("{:.2} % RH", val),
+            _ => format!("{:?}", reading),
+        };
+        println!("  0x{:02x} x {:3}, last: {}", opcode, count, description);
+    }
+    Ok(())
+}
@@ -0,0 +1,118 @@
+//! A minimal Modbus TCP server exposing the sensor values as holding registers:
+//!
+//! | Register | Value                         |
+//! |----------|-------------------------------|
+//! | 0        | CO₂ concentration in ppm      |
+//! | 1        | temperature in 0.01 °C (i16)  |
+//! | 2        | relative humidity in 0.01 %   |
+//!
+//! Only the "Read Holding Registers" (`0x03`) function is supported. The
+//! listening address defaults to `0.0.0.0:5020`, since port 502 usually
+//! needs elevated privileges.
+
+use co2mon::{Sensor, SingleReading};
+use std::env;
+use std::error::Error;
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+const READ_HOLDING_REGISTERS: u8 = 0x03;
+const ILLEGAL_FUNCTION: u8 = 0x01;
+const ILLEGAL_DATA_ADDRESS: u8 = 0x02;
+const ILLEGAL_DATA_VALUE: u8 = 0x03;
+
+// How long to wait before reading again after a failure, e.g. while the
+// sensor is unplugged.
+const ERROR_DELAY: Duration = Duration::from_secs(1);
+
+type Registers = Arc<Mutex<[u16; 3]>>;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let addr = env::args().nth(1).unwrap_or_else(|| "0.0.0.0:5020".into());
+    let sensor = Sensor::open_default()?;
+    let registers = Registers::default();
+
+    let sensor_registers = registers.clone();
+    thread::spawn(move || loop {
+        match sensor.read_one() {
+            Ok(reading) => {
+                let mut registers = sensor_registers.lock().unwrap();
+                match reading {
+                    SingleReading::CO2(val) => registers[0] = val,
+                    SingleReading::Temperature(val) => registers[1] = (val * 100.0) as i16 as u16,
+                    SingleReading::Humidity(val) => registers[2] = (val * 100.0) as u16,
+                    _ => {}
+                }
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                thread::sleep(ERROR_DELAY);
+            }
+        }
+    });
+
+    let listener = TcpListener::bind(&addr)?;
+    println!("listening on {}", addr);
+    for stream in listener.incoming() {
+        let stream = stream?;
+        let registers = registers.clone();
+        thread::spawn(move || {
+            if let Err(e) = serve(stream, &registers) {
+                eprintln!("{}", e);
+            }
+        });
+    }
+    Ok(())
+}
+
+fn serve(mut stream: TcpStream, registers: &Registers) -> io::Result<()> {
+    loop {
+        // MBAP header: transaction id, protocol id, length, unit id
+        let mut header = [0; 7];
+        match stream.read_exact(&mut header) {
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+            result => result?,
+        }
+        let len = usize::from(u16::from_be_bytes([header[4], header[5]]));
+        if len < 2 {
+            return Err(io::ErrorKind::InvalidData.into());
+        }
+        let mut pdu = vec![0; len - 1];
+        stream.read_exact(&mut pdu)?;
+
+        let values = *registers.lock().unwrap();
+        let response = respond(&pdu, &values);
+        let mut frame = Vec::with_capacity(7 + response.len());
+        frame.extend_from_slice(&header[..4]);
+        frame.extend_from_slice(&(response.len() as u16 + 1).to_be_bytes());
+        frame.push(header[6]);
+        frame.extend_from_slice(&response);
+        stream.write_all(&frame)?;
+    }
+}
+
+fn respond(pdu: &[u8], registers: &[u16]) -> Vec<u8> {
+    let function = pdu[0];
+    if function != READ_HOLDING_REGISTERS {
+        return vec![function | 0x80, ILLEGAL_FUNCTION];
+    }
+    if pdu.len() != 5 {
+        return vec![function | 0x80, ILLEGAL_DATA_VALUE];
+    }
+
+    let start = usize::from(u16::from_be_bytes([pdu[1], pdu[2]]));
+    let count = usize::from(u16::from_be_bytes([pdu[3], pdu[4]]));
+    match registers.get(start..start + count) {
+        Some(values) if count > 0 => {
+            let mut response = vec![function, (count * 2) as u8];
+            for value in values {
+                response.extend_from_slice(&value.to_be_bytes());
+            }
+            response
+        }
+        _ => vec![function | 0x80, ILLEGAL_DATA_ADDRESS],
+    }
+}
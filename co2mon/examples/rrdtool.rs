@@ -0,0 +1,41 @@
+//! Prints `rrdtool update` commands, meant to be piped into `rrdtool -`:
+//!
+//! ```text
+//! rrdtool create co2mon.rrd --step 10 \
+//!     DS:temperature:GAUGE:60:-40:100 DS:co2:GAUGE:60:0:10000 \
+//!     RRA:AVERAGE:0.5:1:8640 RRA:AVERAGE:0.5:30:8760
+//! cargo run --example rrdtool co2mon.rrd | rrdtool -
+//! ```
+
+use co2mon::{Result, Sensor};
+use std::env;
+use std::io::{self, Write};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+fn main() -> Result<()> {
+    let file = env::args().nth(1).unwrap_or_else(|| "co2mon.rrd".into());
+    let sensor = Sensor::open_default()?;
+    loop {
+        match sensor.read() {
+            Ok(reading) => {
+                let timestamp = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs();
+                let mut stdout = io::stdout().lock();
+                writeln!(
+                    stdout,
+                    "update {} {}:{:.4}:{}",
+                    file,
+                    timestamp,
+                    reading.temperature(),
+                    reading.co2()
+                )?;
+                stdout.flush()?;
+            }
+            Err(e) => eprintln!("{}", e),
+        }
+        thread::sleep(Duration::from_secs(10));
+    }
+}
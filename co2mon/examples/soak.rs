@@ -0,0 +1,106 @@
+//! Reads from the sensor for a number of hours (`1` by default) and prints a
+//! summary of the error rates, reconnections, read latencies and memory usage:
+//!
+//! ```text
+//! cargo run --release --example soak -- 24
+//! ```
+//!
+//! Disconnected sensors are reopened by the library, using the default
+//! `ReconnectPolicy`.
+
+use co2mon::{OpenOptions, ReconnectPolicy, Result};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::time::{Duration, Instant};
+
+#[derive(Default)]
+struct Stats {
+    reads: u64,
+    errors: BTreeMap<String, u64>,
+    // read latency histogram, in milliseconds
+    latencies: BTreeMap<u128, u64>,
+}
+
+impl Stats {
+    fn print(&self, elapsed: Duration, reconnects: u64) {
+        let errors = self.errors.values().sum::<u64>();
+        println!("elapsed: {:.1} h", elapsed.as_secs_f64() / 3600.0);
+        println!(
+            "reads: {}, errors: {} ({:.3} %), reconnects: {}",
+            self.reads,
+            errors,
+            errors as f64 * 100.0 / self.reads.max(1) as f64,
+            reconnects
+        );
+        for (error, count) in &self.errors {
+            println!("  {}: {}", error, count);
+        }
+
+        if let Some(max) = self.latencies.keys().next_back() {
+            let percentile = |p: u64| {
+                let rank = (self.reads * p).div_ceil(100);
+                let mut seen = 0;
+                for (latency, count) in &self.latencies {
+                    seen += count;
+                    if seen >= rank {
+                        return *latency;
+                    }
+                }
+                *max
+            };
+            println!(
+                "latency: p50 {} ms, p90 {} ms, p99 {} ms, max {} ms",
+                percentile(50),
+                percentile(90),
+                percentile(99),
+                max
+            );
+        }
+        if let Some(rss) = resident_memory() {
+            println!("resident memory: {}", rss);
+        }
+    }
+}
+
+/// Returns the resident set size of the process, on Linux.
+fn resident_memory() -> Option<String> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    Some(line["VmRSS:".len()..].trim().to_string())
+}
+
+fn main() -> Result<()> {
+    let hours = env::args()
+        .nth(1)
+        .and_then(|hours| hours.parse::<f64>().ok())
+        .unwrap_or(1.0);
+    let duration = Duration::from_secs_f64(hours * 3600.0);
+
+    let sensor = OpenOptions::new()
+        .auto_reconnect(ReconnectPolicy::new())
+        .open()?;
+    let mut stats = Stats::default();
+    let start = Instant::now();
+    let mut last_report = start;
+    while start.elapsed() < duration {
+        let read_start = Instant::now();
+        let result = sensor.read_one();
+        *stats
+            .latencies
+            .entry(read_start.elapsed().as_millis())
+            .or_default() += 1;
+        stats.reads += 1;
+        if let Err(e) = result {
+            *stats.errors.entry(e.to_string()).or_default() += 1;
+        }
+
+        if last_report.elapsed() >= Duration::from_secs(3600) {
+            stats.print(start.elapsed(), sensor.stats().reconnects());
+            println!();
+            last_report = Instant::now();
+        }
+    }
+    stats.print(start.elapsed(), sensor.stats().reconnects());
+    Ok(())
+}
@@ -0,0 +1,49 @@
+//! Streams the readings as JSON lines, as they arrive, for piping into other
+//! tools:
+//!
+//! ```text
+//! cargo run --example stream | jq .co2
+//! ```
+//!
+//! The sensor is read in the background by a `Poller`, and a second
+//! subscriber prints the minimum and maximum CO₂ levels seen so far to the
+//! standard error every ten readings.
+
+use co2mon::{Poller, Result, Sensor};
+use std::io::{self, Write};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn main() -> Result<()> {
+    let poller = Poller::with_error_hook(Sensor::open_default()?, |e| eprintln!("{}", e));
+
+    let summary = poller.subscribe();
+    thread::spawn(move || {
+        let (mut min, mut max) = (u16::MAX, 0);
+        for (i, reading) in summary.iter().enumerate() {
+            min = min.min(reading.co2());
+            max = max.max(reading.co2());
+            if i % 10 == 9 {
+                eprintln!("CO₂ between {} and {} ppm", min, max);
+            }
+        }
+    });
+
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    for reading in poller.subscribe() {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        writeln!(
+            stdout,
+            r#"{{"timestamp":{},"temperature":{:.2},"co2":{}}}"#,
+            timestamp,
+            reading.temperature(),
+            reading.co2()
+        )?;
+        stdout.flush()?;
+    }
+    Ok(())
+}
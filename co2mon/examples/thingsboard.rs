@@ -0,0 +1,62 @@
+//! Posts the readings to a ThingsBoard server using the HTTP device API:
+//!
+//! ```text
+//! cargo run --example thingsboard -- demo.thingsboard.io:80 $ACCESS_TOKEN
+//! ```
+//!
+//! Only plain HTTP is supported, put a TLS-terminating proxy in front of the
+//! server if needed.
+
+use co2mon::Sensor;
+use std::env;
+use std::error::Error;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::thread;
+use std::time::Duration;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut args = env::args().skip(1);
+    let (server, token) = match (args.next(), args.next()) {
+        (Some(server), Some(token)) => (server, token),
+        _ => return Err("usage: thingsboard <host:port> <access token>".into()),
+    };
+
+    let sensor = Sensor::open_default()?;
+    loop {
+        match sensor.read() {
+            Ok(reading) => {
+                let body = format!(
+                    r#"{{"temperature":{:.2},"co2":{}}}"#,
+                    reading.temperature(),
+                    reading.co2()
+                );
+                if let Err(e) = post(&server, &format!("/api/v1/{}/telemetry", token), &body) {
+                    eprintln!("{}", e);
+                }
+            }
+            Err(e) => eprintln!("{}", e),
+        }
+        thread::sleep(Duration::from_secs(30));
+    }
+}
+
+fn post(server: &str, path: &str, body: &str) -> Result<(), Box<dyn Error>> {
+    let mut stream = TcpStream::connect(server)?;
+    stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+    write!(
+        stream,
+        "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        path,
+        server,
+        body.len(),
+        body
+    )?;
+
+    let mut status = String::new();
+    BufReader::new(stream).read_line(&mut status)?;
+    match status.split_whitespace().nth(1) {
+        Some("200") => Ok(()),
+        _ => Err(format!("unexpected response: {}", status.trim_end()).into()),
+    }
+}
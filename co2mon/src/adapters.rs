@@ -0,0 +1,415 @@
+//! Iterator adapters to reduce the rate of the readings.
+//!
+//! The sensors send a reading every few seconds, which is often more than a
+//! dashboard or a log needs. [`ReadingsExt`] adds adapters for that to any
+//! iterator over `Result<Reading>`, like [`Sensor::readings`]:
+//!
+//!  - [`ReadingsExt::throttle`] skips the readings that come too soon after
+//!    the previous one
+//!  - [`ReadingsExt::sample_every`] takes one reading on a fixed schedule
+//!  - [`ReadingsExt::debounce`] waits for the readings to settle
+//!  - [`ReadingsExt::distinct_until_changed`] skips the readings that are
+//!    close to the previous one
+//!
+//! The time of a reading is the time at which the adapter receives it.
+//! Errors are passed through as they are, and don't affect the adapters.
+//!
+//! For the channel returned by [`Poller::subscribe`], use
+//! `readings.iter().map(Ok)`.
+//!
+//! [`Sensor::readings`]: crate::Sensor::readings
+//! [`Poller::subscribe`]: crate::Poller::subscribe
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use co2mon::adapters::{ReadingsExt, Tolerance};
+//! # use co2mon::{Result, Sensor};
+//! # use std::time::Duration;
+//! # #[cfg(feature = "hid")]
+//! # fn main() -> Result<()> {
+//! #
+//! let sensor = Sensor::open_default()?;
+//! let readings = sensor
+//!     .readings()
+//!     .distinct_until_changed(Tolerance::new(20, 0.5))
+//!     .throttle(Duration::from_secs(60));
+//! for reading in readings {
+//!     println!("{} ppm CO₂", reading?.co2());
+//! }
+//! #
+//! # Ok(())
+//! # }
+//! # #[cfg(not(feature = "hid"))]
+//! # fn main() {}
+//! ```
+
+use crate::clock::{Clock, SystemClock};
+use crate::{Reading, Result};
+use std::iter::FusedIterator;
+use std::time::{Duration, Instant};
+
+/// The largest differences between two readings that are not considered a
+/// change.
+///
+/// See [`ReadingsExt::distinct_until_changed`].
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Tolerance {
+    co2: u16,
+    temperature: f32,
+}
+
+impl Tolerance {
+    /// Creates a tolerance of `co2` ppm and `temperature` °C.
+    pub fn new(co2: u16, temperature: f32) -> Self {
+        Self { co2, temperature }
+    }
+
+    /// Returns the tolerance of the CO₂ concentration, in ppm.
+    pub fn co2(&self) -> u16 {
+        self.co2
+    }
+
+    /// Returns the tolerance of the temperature, in °C.
+    pub fn temperature(&self) -> f32 {
+        self.temperature
+    }
+
+    fn exceeded(&self, a: &Reading, b: &Reading) -> bool {
+        let co2 = (i32::from(a.co2()) - i32::from(b.co2())).unsigned_abs();
+        co2 > u32::from(self.co2) || (a.temperature() - b.temperature()).abs() > self.temperature
+    }
+}
+
+/// Adapters for the iterators over readings.
+///
+/// See the [module documentation][crate::adapters].
+pub trait ReadingsExt: Iterator<Item = Result<Reading>> + Sized {
+    /// Skips the readings that arrive less than `interval` after the last
+    /// one returned.
+    ///
+    /// The first reading is always returned.
+    fn throttle(self, interval: Duration) -> Throttle<Self> {
+        Throttle::new(self, interval, Box::new(SystemClock))
+    }
+
+    /// Returns the first reading, then the first reading after every
+    /// multiple of `interval` since then.
+    ///
+    /// Unlike [`ReadingsExt::throttle`], the schedule doesn't shift when the
+    /// readings arrive late, and the intervals without any reading are
+    /// skipped.
+    fn sample_every(self, interval: Duration) -> SampleEvery<Self> {
+        SampleEvery::new(self, interval, Box::new(SystemClock))
+    }
+
+    /// Returns a reading once the readings have stayed the same for `quiet`,
+    /// and then each time they settle on a different value.
+    ///
+    /// This can wait indefinitely if the readings keep changing, so
+    /// it's best used on rounded values, or after
+    /// [`ReadingsExt::distinct_until_changed`].
+    fn debounce(self, quiet: Duration) -> Debounce<Self> {
+        Debounce::new(self, quiet, Box::new(SystemClock))
+    }
+
+    /// Skips the readings that are within `tolerance` of the last one
+    /// returned.
+    ///
+    /// The comparison is against the last reading returned, so slow drifts
+    /// are still noticed.
+    fn distinct_until_changed(self, tolerance: Tolerance) -> DistinctUntilChanged<Self> {
+        DistinctUntilChanged {
+            iter: self,
+            tolerance,
+            last: None,
+        }
+    }
+}
+
+impl<I: Iterator<Item = Result<Reading>>> ReadingsExt for I {}
+
+/// An iterator skipping the readings that come too soon.
+///
+/// See [`ReadingsExt::throttle`].
+pub struct Throttle<I> {
+    iter: I,
+    interval: Duration,
+    last: Option<Instant>,
+    clock: Box<dyn Clock>,
+}
+
+impl<I> Throttle<I> {
+    fn new(iter: I, interval: Duration, clock: Box<dyn Clock>) -> Self {
+        Self {
+            iter,
+            interval,
+            last: None,
+            clock,
+        }
+    }
+}
+
+impl<I: Iterator<Item = Result<Reading>>> Iterator for Throttle<I> {
+    type Item = Result<Reading>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let reading = match self.iter.next()? {
+                Ok(reading) => reading,
+                Err(e) => return Some(Err(e)),
+            };
+            let now = self.clock.now();
+            if let Some(last) = self.last {
+                if now.duration_since(last) < self.interval {
+                    continue;
+                }
+            }
+            self.last = Some(now);
+            return Some(Ok(reading));
+        }
+    }
+}
+
+impl<I: FusedIterator<Item = Result<Reading>>> FusedIterator for Throttle<I> {}
+
+/// An iterator taking one reading per interval.
+///
+/// See [`ReadingsExt::sample_every`].
+pub struct SampleEvery<I> {
+    iter: I,
+    interval: Duration,
+    tick: Option<Instant>,
+    clock: Box<dyn Clock>,
+}
+
+impl<I> SampleEvery<I> {
+    fn new(iter: I, interval: Duration, clock: Box<dyn Clock>) -> Self {
+        Self {
+            iter,
+            interval,
+            tick: None,
+            clock,
+        }
+    }
+}
+
+impl<I: Iterator<Item = Result<Reading>>> Iterator for SampleEvery<I> {
+    type Item = Result<Reading>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let reading = match self.iter.next()? {
+                Ok(reading) => reading,
+                Err(e) => return Some(Err(e)),
+            };
+            let now = self.clock.now();
+            if let Some(tick) = self.tick {
+                let elapsed = now.duration_since(tick);
+                if elapsed < self.interval {
+                    continue;
+                }
+                if self.interval > Duration::ZERO {
+                    // move to the last tick before now, skipping the missed ones
+                    let phase = elapsed.as_nanos() % self.interval.as_nanos();
+                    self.tick = Some(now - Duration::from_nanos(phase as u64));
+                    return Some(Ok(reading));
+                }
+            }
+            self.tick = Some(now);
+            return Some(Ok(reading));
+        }
+    }
+}
+
+impl<I: FusedIterator<Item = Result<Reading>>> FusedIterator for SampleEvery<I> {}
+
+/// An iterator waiting for the readings to settle.
+///
+/// See [`ReadingsExt::debounce`].
+pub struct Debounce<I> {
+    iter: I,
+    quiet: Duration,
+    candidate: Option<(Reading, Instant)>,
+    last: Option<Reading>,
+    clock: Box<dyn Clock>,
+}
+
+impl<I> Debounce<I> {
+    fn new(iter: I, quiet: Duration, clock: Box<dyn Clock>) -> Self {
+        Self {
+            iter,
+            quiet,
+            candidate: None,
+            last: None,
+            clock,
+        }
+    }
+}
+
+impl<I: Iterator<Item = Result<Reading>>> Iterator for Debounce<I> {
+    type Item = Result<Reading>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let reading = match self.iter.next()? {
+                Ok(reading) => reading,
+                Err(e) => return Some(Err(e)),
+            };
+            let now = self.clock.now();
+            let since = match &self.candidate {
+                Some((candidate, since)) if *candidate == reading => *since,
+                _ => {
+                    self.candidate = Some((reading.clone(), now));
+                    now
+                }
+            };
+            if now.duration_since(since) >= self.quiet && self.last.as_ref() != Some(&reading) {
+                self.last = Some(reading.clone());
+                return Some(Ok(reading));
+            }
+        }
+    }
+}
+
+impl<I: FusedIterator<Item = Result<Reading>>> FusedIterator for Debounce<I> {}
+
+/// An iterator skipping the readings that didn't change enough.
+///
+/// See [`ReadingsExt::distinct_until_changed`].
+#[derive(Debug, Clone)]
+pub struct DistinctUntilChanged<I> {
+    iter: I,
+    tolerance: Tolerance,
+    last: Option<Reading>,
+}
+
+impl<I: Iterator<Item = Result<Reading>>> Iterator for DistinctUntilChanged<I> {
+    type Item = Result<Reading>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let reading = match self.iter.next()? {
+                Ok(reading) => reading,
+                Err(e) => return Some(Err(e)),
+            };
+            if let Some(last) = &self.last {
+                if !self.tolerance.exceeded(last, &reading) {
+                    continue;
+                }
+            }
+            self.last = Some(reading.clone());
+            return Some(Ok(reading));
+        }
+    }
+}
+
+impl<I: FusedIterator<Item = Result<Reading>>> FusedIterator for DistinctUntilChanged<I> {}
+
+#[cfg(test)]
+mod tests {
+    use super::{Debounce, ReadingsExt, SampleEvery, Throttle, Tolerance};
+    use crate::fixtures::fake_clock;
+    use crate::{Error, Reading, Result};
+    use std::time::Duration;
+
+    fn reading(temperature: f32, co2: u16) -> Reading {
+        Reading { temperature, co2 }
+    }
+
+    /// Returns readings with the given CO₂ concentrations and a constant
+    /// temperature.
+    fn readings(co2: &[u16]) -> impl Iterator<Item = Result<Reading>> + '_ {
+        co2.iter().map(|&co2| Ok(reading(21.0, co2)))
+    }
+
+    fn co2(readings: impl Iterator<Item = Result<Reading>>) -> Vec<u16> {
+        readings.map(|reading| reading.unwrap().co2()).collect()
+    }
+
+    #[test]
+    fn test_throttle() {
+        // the readings arrive every 2 seconds
+        let input = readings(&[0, 2, 4, 6, 8, 10, 12]);
+        let throttle = Throttle::new(
+            input,
+            Duration::from_secs(3),
+            fake_clock(Duration::from_secs(2)),
+        );
+        assert_eq!(co2(throttle), [0, 4, 8, 12]);
+    }
+
+    #[test]
+    fn test_sample_every() {
+        let input = readings(&[0, 2, 4, 6, 8, 10, 12]);
+        let sample = SampleEvery::new(
+            input,
+            Duration::from_secs(3),
+            fake_clock(Duration::from_secs(2)),
+        );
+        assert_eq!(co2(sample), [0, 4, 6, 10, 12]);
+
+        // the intervals without readings are skipped
+        let input = readings(&[0, 10, 20, 30]);
+        let sample = SampleEvery::new(
+            input,
+            Duration::from_secs(3),
+            fake_clock(Duration::from_secs(10)),
+        );
+        assert_eq!(co2(sample), [0, 10, 20, 30]);
+
+        let input = readings(&[0, 1, 2]);
+        let sample = SampleEvery::new(input, Duration::ZERO, fake_clock(Duration::from_secs(1)));
+        assert_eq!(co2(sample), [0, 1, 2]);
+    }
+
+    #[test]
+    fn test_debounce() {
+        // the readings arrive every second
+        let input = readings(&[400, 400, 400, 410, 420, 410, 420, 420, 420, 420]);
+        let debounce = Debounce::new(
+            input,
+            Duration::from_secs(2),
+            fake_clock(Duration::from_secs(1)),
+        );
+        assert_eq!(co2(debounce), [400, 420]);
+
+        // the same value isn't returned twice in a row
+        let input = readings(&[400, 400, 400, 410, 400, 400, 400]);
+        let debounce = Debounce::new(
+            input,
+            Duration::from_secs(2),
+            fake_clock(Duration::from_secs(1)),
+        );
+        assert_eq!(co2(debounce), [400]);
+    }
+
+    #[test]
+    fn test_distinct_until_changed() {
+        let input = readings(&[400, 410, 420, 421, 400, 379]);
+        let distinct = input.distinct_until_changed(Tolerance::new(20, 0.0));
+        assert_eq!(co2(distinct), [400, 421, 400, 379]);
+
+        let input = [21.0, 21.25, 21.5, 21.0625]
+            .iter()
+            .map(|&temperature| Ok(reading(temperature, 400)));
+        let distinct = input.distinct_until_changed(Tolerance::new(0, 0.25));
+        let temperatures = distinct
+            .map(|reading| reading.unwrap().temperature())
+            .collect::<Vec<_>>();
+        assert_eq!(temperatures, [21.0, 21.5, 21.0625]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let input = vec![
+            Ok(reading(21.0, 400)),
+            Err(Error::Timeout),
+            Ok(reading(21.0, 400)),
+        ];
+        let mut throttle = input.into_iter().throttle(Duration::from_secs(60));
+        assert!(throttle.next().unwrap().is_ok());
+        assert!(matches!(throttle.next(), Some(Err(Error::Timeout))));
+        assert!(throttle.next().is_none());
+    }
+}
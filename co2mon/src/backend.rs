@@ -0,0 +1,168 @@
+#[cfg(feature = "hid")]
+use crate::Error;
+use crate::Result;
+#[cfg(feature = "hid")]
+use hidapi::HidDevice;
+use std::cell::RefCell;
+use std::io::{self, Read};
+
+/// A transport delivering raw reports from the sensor.
+///
+/// [`Sensor`][crate::Sensor] only talks to the device through this trait,
+/// which keeps the protocol handling independent of how the reports are
+/// obtained. It is implemented for [`hidapi::HidDevice`], and custom
+/// implementations (e.g. a fake device scripting partial reads, timeouts or
+/// disconnects in tests) can be used with
+/// [`OpenOptions::open_backend`][crate::OpenOptions::open_backend].
+///
+/// # Example
+///
+/// ```
+/// # use co2mon::{Backend, OpenOptions, Result, SingleReading};
+/// struct Replay([u8; 8]);
+///
+/// impl Backend for Replay {
+///     fn send_feature_report(&self, _data: &[u8]) -> Result<()> {
+///         Ok(())
+///     }
+///
+///     fn read_timeout(&self, buf: &mut [u8], _timeout: i32) -> Result<usize> {
+///         buf.copy_from_slice(&self.0);
+///         Ok(buf.len())
+///     }
+/// }
+///
+/// # fn main() -> Result<()> {
+/// #
+/// let report = [0x50, 0x04, 0x57, 0xab, 0x0d, 0x00, 0x00, 0x00];
+/// let sensor = OpenOptions::new().open_backend(Replay(report))?;
+/// assert_eq!(sensor.read_one()?, SingleReading::CO2(1111));
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub trait Backend: Send {
+    /// Sends a feature report, including the leading Report ID byte.
+    fn send_feature_report(&self, data: &[u8]) -> Result<()>;
+
+    /// Reads an input report, waiting for at most `timeout` milliseconds
+    /// (`-1` means blocking).
+    ///
+    /// Returns the number of bytes read, `0` meaning that the timeout expired.
+    fn read_timeout(&self, buf: &mut [u8], timeout: i32) -> Result<usize>;
+}
+
+#[cfg(feature = "hid")]
+impl Backend for HidDevice {
+    fn send_feature_report(&self, data: &[u8]) -> Result<()> {
+        HidDevice::send_feature_report(self, data).map_err(Error::from_transfer)
+    }
+
+    fn read_timeout(&self, buf: &mut [u8], timeout: i32) -> Result<usize> {
+        // leave room for a report ID
+        let mut report = [0; MAX_REPORT_LEN + 1];
+        let report = &mut report[..buf.len().min(MAX_REPORT_LEN) + 1];
+        let len = HidDevice::read_timeout(self, report, timeout).map_err(Error::from_transfer)?;
+        strip_report_id(&report[..len], buf)
+    }
+}
+
+/// The largest input report of a full-speed USB HID device.
+#[cfg(feature = "hid")]
+const MAX_REPORT_LEN: usize = 64;
+
+/// Copies a report to `buf`, dropping the leading report ID which some
+/// `hidapi` backends on Windows keep.
+#[cfg(feature = "hid")]
+fn strip_report_id(report: &[u8], buf: &mut [u8]) -> Result<usize> {
+    let data = match report {
+        [0, data @ ..] if data.len() == buf.len() => data,
+        data if data.len() <= buf.len() => data,
+        _ => return Err(Error::InvalidMessage),
+    };
+    buf[..data.len()].copy_from_slice(data);
+    Ok(data.len())
+}
+
+/// Reads the plain 5-byte frames sent by the ZG modules over a serial line.
+pub(crate) struct StreamBackend<R> {
+    stream: RefCell<R>,
+}
+
+impl<R> StreamBackend<R> {
+    pub(crate) fn new(stream: R) -> Self {
+        Self {
+            stream: RefCell::new(stream),
+        }
+    }
+}
+
+impl<R: Read + Send> Backend for StreamBackend<R> {
+    fn send_feature_report(&self, _data: &[u8]) -> Result<()> {
+        // the modules start sending data as soon as they're powered on
+        Ok(())
+    }
+
+    fn read_timeout(&self, buf: &mut [u8], _timeout: i32) -> Result<usize> {
+        let mut stream = self.stream.borrow_mut();
+        let mut frame = [0; 5];
+        let mut filled = 0;
+        loop {
+            match stream.read(&mut frame[filled..]) {
+                Ok(0) => return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()),
+                Ok(len) => filled += len,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) if is_timeout(&e) => return Ok(0),
+                Err(e) => return Err(e.into()),
+            }
+            if filled < frame.len() {
+                continue;
+            }
+
+            // resynchronize on the terminator when starting in the middle of a frame
+            if zg_co2::decode(frame).is_ok() {
+                break;
+            }
+            frame.rotate_left(1);
+            filled -= 1;
+        }
+
+        // pad to the size of a HID report, the frame is recognized as unencrypted
+        buf[..frame.len()].copy_from_slice(&frame);
+        buf[frame.len()..].fill(0);
+        Ok(buf.len())
+    }
+}
+
+fn is_timeout(err: &io::Error) -> bool {
+    matches!(
+        err.kind(),
+        io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock
+    )
+}
+
+#[cfg(all(test, feature = "hid"))]
+mod tests {
+    use crate::Error;
+
+    #[test]
+    fn test_strip_report_id() {
+        let report = [0x50, 0x04, 0x57, 0xab, 0x0d, 0x00, 0x00, 0x00];
+        let mut buf = [0; 8];
+        assert_eq!(super::strip_report_id(&report, &mut buf).unwrap(), 8);
+        assert_eq!(buf, report);
+
+        let mut buf = [0; 8];
+        let with_id = [0, 0x50, 0x04, 0x57, 0xab, 0x0d, 0x00, 0x00, 0x00];
+        assert_eq!(super::strip_report_id(&with_id, &mut buf).unwrap(), 8);
+        assert_eq!(buf, report);
+
+        assert_eq!(super::strip_report_id(&[], &mut buf).unwrap(), 0);
+        assert_eq!(super::strip_report_id(&report[..5], &mut buf).unwrap(), 5);
+        let bad_id = [1, 0x50, 0x04, 0x57, 0xab, 0x0d, 0x00, 0x00, 0x00];
+        assert!(matches!(
+            super::strip_report_id(&bad_id, &mut buf),
+            Err(Error::InvalidMessage)
+        ));
+    }
+}
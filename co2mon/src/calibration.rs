@@ -0,0 +1,185 @@
+//! Linear correction of the CO₂ readings against a reference sensor.
+//!
+//! The NDIR sensors used in these devices can drift or have a slight gain
+//! error compared to a calibrated instrument (e.g. a Senseair or SCD30).
+//! [`Fit`] collects pairs of measured and reference values and estimates a
+//! [`Calibration`] using least squares, which is then applied to the
+//! readings.
+//!
+//! The crate does not store the coefficients itself. To keep them across
+//! restarts, save [`Calibration::gain`] and [`Calibration::offset`] and pass
+//! them back to [`Calibration::new`].
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use co2mon::calibration::Fit;
+//! # use co2mon::{Result, Sensor};
+//! # fn read_reference() -> u16 { 0 }
+//! # #[cfg(feature = "hid")]
+//! # fn main() -> Result<()> {
+//! #
+//! let sensor = Sensor::open_default()?;
+//! let mut fit = Fit::new();
+//! for _ in 0..10 {
+//!     fit.add(sensor.read_co2()?, read_reference());
+//! }
+//! let calibration = fit.calibration().unwrap();
+//! let reading = calibration.apply(&sensor.read()?);
+//! println!("{} ppm CO₂", reading.co2());
+//! #
+//! # Ok(())
+//! # }
+//! # #[cfg(not(feature = "hid"))]
+//! # fn main() {}
+//! ```
+
+use crate::Reading;
+
+/// A linear correction of the CO₂ concentration:
+/// `corrected = gain * measured + offset`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Calibration {
+    gain: f64,
+    offset: f64,
+}
+
+impl Calibration {
+    /// Creates a calibration with the given coefficients.
+    pub fn new(gain: f64, offset: f64) -> Self {
+        Self { gain, offset }
+    }
+
+    /// Returns the gain.
+    pub fn gain(&self) -> f64 {
+        self.gain
+    }
+
+    /// Returns the offset, in ppm.
+    pub fn offset(&self) -> f64 {
+        self.offset
+    }
+
+    /// Corrects a CO₂ concentration, in ppm.
+    ///
+    /// The result is rounded and saturates at the bounds of `u16`.
+    pub fn apply_co2(&self, co2: u16) -> u16 {
+        // float to integer casts saturate
+        (self.gain * f64::from(co2) + self.offset).round() as u16
+    }
+
+    /// Corrects the CO₂ concentration of a reading.
+    ///
+    /// The temperature is left unchanged.
+    pub fn apply(&self, reading: &Reading) -> Reading {
+        Reading {
+            temperature: reading.temperature,
+            co2: self.apply_co2(reading.co2),
+        }
+    }
+}
+
+impl Default for Calibration {
+    /// Returns the identity calibration.
+    fn default() -> Self {
+        Self::new(1.0, 0.0)
+    }
+}
+
+/// Estimates a [`Calibration`] from pairs of measured and reference values.
+#[derive(Debug, Clone, Default)]
+pub struct Fit {
+    samples: u32,
+    sum_x: f64,
+    sum_y: f64,
+    sum_xx: f64,
+    sum_xy: f64,
+}
+
+impl Fit {
+    /// Creates an empty fit.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a CO₂ concentration measured by this device, along with the one
+    /// reported by the reference sensor at the same time.
+    pub fn add(&mut self, measured: u16, reference: u16) {
+        let x = f64::from(measured);
+        let y = f64::from(reference);
+        self.samples += 1;
+        self.sum_x += x;
+        self.sum_y += y;
+        self.sum_xx += x * x;
+        self.sum_xy += x * y;
+    }
+
+    /// Returns the number of samples added so far.
+    pub fn samples(&self) -> u32 {
+        self.samples
+    }
+
+    /// Returns the calibration that best matches the samples.
+    ///
+    /// When all the measured values are equal, e.g. with a single sample,
+    /// the gain can't be estimated and only an offset is applied. `None` is
+    /// returned if there are no samples.
+    pub fn calibration(&self) -> Option<Calibration> {
+        if self.samples == 0 {
+            return None;
+        }
+
+        let n = f64::from(self.samples);
+        let mean_x = self.sum_x / n;
+        let mean_y = self.sum_y / n;
+        let var_x = self.sum_xx / n - mean_x * mean_x;
+        let cov_xy = self.sum_xy / n - mean_x * mean_y;
+        // allow for rounding errors when the values are (almost) equal
+        let gain = if var_x > 1e-6 { cov_xy / var_x } else { 1.0 };
+        Some(Calibration::new(gain, mean_y - gain * mean_x))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Calibration, Fit};
+    use crate::Reading;
+
+    #[test]
+    fn test_apply() {
+        let calibration = Calibration::new(1.1, -50.0);
+        assert_eq!(calibration.apply_co2(1000), 1050);
+        assert_eq!(calibration.apply_co2(0), 0);
+        assert_eq!(calibration.apply_co2(u16::MAX), u16::MAX);
+
+        let reading = Reading {
+            temperature: 21.5,
+            co2: 400,
+        };
+        let corrected = calibration.apply(&reading);
+        assert_eq!(corrected.co2(), 390);
+        assert_eq!(corrected.temperature(), 21.5);
+
+        assert_eq!(Calibration::default().apply(&reading), reading);
+    }
+
+    #[test]
+    fn test_fit() {
+        let mut fit = Fit::new();
+        assert!(fit.calibration().is_none());
+
+        fit.add(500, 520);
+        let calibration = fit.calibration().unwrap();
+        assert_eq!(calibration.gain(), 1.0);
+        assert_eq!(calibration.offset(), 20.0);
+
+        for &measured in &[600, 800, 1200] {
+            fit.add(measured, measured + measured / 10 - 30);
+        }
+        let calibration = fit.calibration().unwrap();
+        assert_eq!(fit.samples(), 4);
+        assert!((calibration.gain() - 1.1).abs() < 1e-9);
+        assert!((calibration.offset() + 30.0).abs() < 1e-6);
+        assert_eq!(calibration.apply_co2(1000), 1070);
+    }
+}
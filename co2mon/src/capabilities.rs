@@ -0,0 +1,29 @@
+/// The kinds of readings a device was seen to produce.
+///
+/// See [`Sensor::capabilities`][crate::Sensor::capabilities].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    pub(crate) co2: bool,
+    pub(crate) temperature: bool,
+    pub(crate) humidity: bool,
+}
+
+impl Capabilities {
+    /// Returns whether the device reports the CO₂ concentration.
+    pub fn co2(&self) -> bool {
+        self.co2
+    }
+
+    /// Returns whether the device reports the temperature.
+    pub fn temperature(&self) -> bool {
+        self.temperature
+    }
+
+    /// Returns whether the device reports the relative humidity.
+    ///
+    /// The constant zero humidity readings of the devices without a humidity
+    /// sensor don't count.
+    pub fn humidity(&self) -> bool {
+        self.humidity
+    }
+}
@@ -0,0 +1,29 @@
+#[cfg(feature = "hid")]
+use std::thread;
+#[cfg(feature = "hid")]
+use std::time::Duration;
+use std::time::Instant;
+
+/// A source of the current time, used for the timeout logic.
+///
+/// This exists so that the timeouts can be tested without waiting.
+pub(crate) trait Clock: Send + Sync {
+    fn now(&self) -> Instant;
+
+    #[cfg(feature = "hid")]
+    fn sleep(&self, duration: Duration);
+}
+
+/// The real, monotonic clock.
+pub(crate) struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    #[cfg(feature = "hid")]
+    fn sleep(&self, duration: Duration) {
+        thread::sleep(duration);
+    }
+}
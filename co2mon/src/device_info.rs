@@ -0,0 +1,170 @@
+use std::ffi::{CStr, CString};
+#[cfg(target_os = "linux")]
+use std::fs;
+#[cfg(target_os = "linux")]
+use std::path::Path;
+
+/// Information about a connected sensor device.
+///
+/// See [`Sensor::enumerate`][crate::Sensor::enumerate].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceInfo {
+    path: CString,
+    vendor_id: u16,
+    product_id: u16,
+    serial_number: Option<String>,
+    manufacturer_string: Option<String>,
+    product_string: Option<String>,
+    release_number: u16,
+    usb_location: Option<(u8, Vec<u8>)>,
+}
+
+impl DeviceInfo {
+    pub(crate) fn from_hid(info: &hidapi::DeviceInfo) -> Self {
+        Self {
+            path: info.path().to_owned(),
+            vendor_id: info.vendor_id(),
+            product_id: info.product_id(),
+            serial_number: info.serial_number().map(String::from),
+            manufacturer_string: info.manufacturer_string().map(String::from),
+            product_string: info.product_string().map(String::from),
+            release_number: info.release_number(),
+            usb_location: usb_location(info.path()),
+        }
+    }
+
+    /// Returns the platform-specific device path, which can be passed to
+    /// [`OpenOptions::with_path`][crate::OpenOptions::with_path].
+    pub fn path(&self) -> &CString {
+        &self.path
+    }
+
+    /// Returns the USB Vendor ID.
+    pub fn vendor_id(&self) -> u16 {
+        self.vendor_id
+    }
+
+    /// Returns the USB Product ID.
+    pub fn product_id(&self) -> u16 {
+        self.product_id
+    }
+
+    /// Returns the serial number, which appears to be the firmware version.
+    pub fn serial_number(&self) -> Option<&str> {
+        self.serial_number.as_deref()
+    }
+
+    /// Returns the manufacturer name.
+    pub fn manufacturer_string(&self) -> Option<&str> {
+        self.manufacturer_string.as_deref()
+    }
+
+    /// Returns the product name.
+    pub fn product_string(&self) -> Option<&str> {
+        self.product_string.as_deref()
+    }
+
+    /// Returns the device release number, in binary-coded decimal.
+    pub fn release_number(&self) -> u16 {
+        self.release_number
+    }
+
+    /// Returns the USB bus number and the chain of hub ports the device is
+    /// connected to, which can be passed to
+    /// [`OpenOptions::with_usb_location`][crate::OpenOptions::with_usb_location].
+    ///
+    /// This is only known with the `libusb` and `hidraw` backends.
+    pub fn usb_location(&self) -> Option<(u8, &[u8])> {
+        self.usb_location
+            .as_ref()
+            .map(|(bus, ports)| (*bus, ports.as_slice()))
+    }
+
+    /// Returns the physical port path of the device, like `1-2.3` for port 3
+    /// of the hub on port 2 of bus 1, as used by Linux in `sysfs`.
+    ///
+    /// Unlike the serial number, this tells apart identical sensors, as long
+    /// as they stay plugged into the same ports. It is only known when
+    /// [`DeviceInfo::usb_location`] is.
+    pub fn port_path(&self) -> Option<String> {
+        self.usb_location
+            .as_ref()
+            .map(|(bus, ports)| port_path(*bus, ports))
+    }
+}
+
+/// Formats a USB bus number and port chain like `1-2.3`.
+fn port_path(bus: u8, ports: &[u8]) -> String {
+    let ports = ports
+        .iter()
+        .map(|port| port.to_string())
+        .collect::<Vec<_>>();
+    format!("{}-{}", bus, ports.join("."))
+}
+
+/// Finds the USB bus number and port chain of a device.
+///
+/// The `libusb` backend uses paths like `1-2.3:1.0`, which contain them. With
+/// `hidraw`, they are in the `sysfs` path of the device.
+pub(crate) fn usb_location(path: &CStr) -> Option<(u8, Vec<u8>)> {
+    let path = path.to_str().ok()?;
+    parse_usb_location(path).or_else(|| hidraw_usb_location(path))
+}
+
+#[cfg(target_os = "linux")]
+fn hidraw_usb_location(path: &str) -> Option<(u8, Vec<u8>)> {
+    let name = path.strip_prefix("/dev/")?;
+    let device = fs::canonicalize(Path::new("/sys/class/hidraw").join(name).join("device")).ok()?;
+    // the closest USB interface, e.g. `.../usb1/1-2/1-2.3/1-2.3:1.0/0003:04D9:A052.0001`
+    device
+        .iter()
+        .rev()
+        .filter_map(|component| component.to_str())
+        .find_map(parse_usb_location)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn hidraw_usb_location(_path: &str) -> Option<(u8, Vec<u8>)> {
+    None
+}
+
+/// Parses a USB interface name like `1-2.3:1.0` into the bus number and the
+/// port chain.
+fn parse_usb_location(s: &str) -> Option<(u8, Vec<u8>)> {
+    let (device, interface) = s.split_once(':')?;
+    let (config, interface) = interface.split_once('.')?;
+    config.parse::<u8>().ok()?;
+    interface.parse::<u8>().ok()?;
+
+    let (bus, ports) = device.split_once('-')?;
+    let bus = bus.parse().ok()?;
+    let ports = ports
+        .split('.')
+        .map(|port| port.parse().ok())
+        .collect::<Option<Vec<u8>>>()?;
+    Some((bus, ports))
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_parse_usb_location() {
+        use super::parse_usb_location;
+
+        assert_eq!(parse_usb_location("1-2:1.0"), Some((1, vec![2])));
+        assert_eq!(parse_usb_location("3-1.4.2:1.0"), Some((3, vec![1, 4, 2])));
+        assert_eq!(parse_usb_location("0003:04D9:A052.0001"), None);
+        assert_eq!(parse_usb_location("/dev/hidraw0"), None);
+        assert_eq!(parse_usb_location("1-:1.0"), None);
+    }
+
+    #[test]
+    fn test_port_path() {
+        use super::{parse_usb_location, port_path};
+
+        assert_eq!(port_path(1, &[2]), "1-2");
+        assert_eq!(port_path(3, &[1, 4, 2]), "3-1.4.2");
+        let (bus, ports) = parse_usb_location("3-1.4.2:1.0").unwrap();
+        assert_eq!(port_path(bus, &ports), "3-1.4.2");
+    }
+}
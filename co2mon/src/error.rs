@@ -1,35 +1,197 @@
+#[cfg(feature = "hid")]
 use hidapi::HidError;
 use std::error;
 use std::fmt::{self, Display, Formatter};
+use std::io;
 
 /// A possible error value when opening the sensor or taking a reading.
 #[derive(Debug)]
 #[non_exhaustive]
 pub enum Error {
-    /// A hardware access error.
+    /// A hardware access error which doesn't fit the more specific variants.
+    #[cfg(feature = "hid")]
     Hid(Box<HidError>),
-    /// The sensor returned an invalid message or a single read timeout
-    /// expired.
+    /// No connected device matched the options.
+    DeviceNotFound,
+    /// The device was found, but the current user is not allowed to open
+    /// it. On Linux, this usually means that the `udev` rule from the crate
+    /// documentation is missing.
+    PermissionDenied,
+    /// The device stopped responding, usually because it was unplugged.
+    ///
+    /// See [`Sensor::reconnect`][crate::Sensor::reconnect].
+    Disconnected,
+    /// An I/O error on a stream opened with
+    /// [`OpenOptions::open_stream`][crate::OpenOptions::open_stream].
+    Io(io::Error),
+    /// The sensor returned an invalid message.
     InvalidMessage,
     /// A checksum error.
     Checksum,
-    /// The sensor did not report all values before the timeout expired.
-    ///
-    /// Note that this can only occur when calling
-    /// [`Sensor::read`][crate::Sensor::read].
-    /// [`Sensor::read_one`][crate::Sensor::read_one] returns
-    /// [`Error::InvalidMessage`] on timeout.
+    /// The sensor did not report in time, or did not report all values
+    /// before the timeout expired or the deadline passed.
     Timeout,
-    /// The configured timeout was too large.
+    /// The configured timeout was zero, shorter than a millisecond, or too
+    /// large.
     InvalidTimeout,
+    /// The configured serial number was empty.
+    InvalidSerialNumber,
+    /// The configured device path was empty.
+    InvalidPath,
+    /// The configured list of USB Product IDs was empty.
+    InvalidProductIds,
+    /// The configured USB location had no hub ports.
+    InvalidUsbLocation,
+    /// The environment variable with this name could not be parsed, see
+    /// [`OpenOptions::from_env`][crate::OpenOptions::from_env].
+    InvalidEnvVar(&'static str),
+    /// The operation is not supported for this sensor, e.g. reconnecting a
+    /// sensor that was not opened from a USB device.
+    Unsupported,
 }
 
+#[cfg(feature = "hid")]
 impl From<HidError> for Error {
     fn from(err: HidError) -> Self {
         Error::Hid(Box::new(err))
     }
 }
 
+/// The category of an [`Error`], as returned by [`Error::kind`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The device could not be opened or accessed, or stopped responding.
+    ///
+    /// Reading again is unlikely to help, but reconnecting after a delay
+    /// might, see [`Sensor::reconnect`][crate::Sensor::reconnect].
+    Connectivity,
+    /// The device sent a corrupted or unexpected report. The next one is
+    /// usually fine.
+    Protocol,
+    /// The device did not send the expected readings in time.
+    Timeout,
+    /// The options or the arguments were not valid. Retrying with the same
+    /// ones always fails.
+    Configuration,
+    /// The operation is not supported for this sensor.
+    Unsupported,
+}
+
+impl Error {
+    /// Returns the category of the error.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use co2mon::{ErrorKind, Result, Sensor};
+    /// # use std::thread;
+    /// # use std::time::Duration;
+    /// # #[cfg(feature = "hid")]
+    /// # fn main() -> Result<()> {
+    /// #
+    /// let mut sensor = Sensor::open_default()?;
+    /// loop {
+    ///     match sensor.read() {
+    ///         Ok(reading) => println!("{} ppm CO₂", reading.co2()),
+    ///         Err(e) if e.kind() == ErrorKind::Connectivity => {
+    ///             thread::sleep(Duration::from_secs(5));
+    ///             sensor.reconnect()?;
+    ///         }
+    ///         Err(e) if e.is_transient() => continue,
+    ///         Err(e) => return Err(e),
+    ///     }
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "hid"))]
+    /// # fn main() {}
+    /// ```
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            #[cfg(feature = "hid")]
+            Error::Hid(_) => ErrorKind::Connectivity,
+            Error::Io(_)
+            | Error::DeviceNotFound
+            | Error::PermissionDenied
+            | Error::Disconnected => ErrorKind::Connectivity,
+            Error::InvalidMessage | Error::Checksum => ErrorKind::Protocol,
+            Error::Timeout => ErrorKind::Timeout,
+            Error::InvalidTimeout
+            | Error::InvalidSerialNumber
+            | Error::InvalidPath
+            | Error::InvalidProductIds
+            | Error::InvalidUsbLocation
+            | Error::InvalidEnvVar(_) => ErrorKind::Configuration,
+            Error::Unsupported => ErrorKind::Unsupported,
+        }
+    }
+
+    /// Returns `true` if the same operation might succeed when retried right
+    /// away, i.e. for the [`ErrorKind::Protocol`] and [`ErrorKind::Timeout`]
+    /// errors.
+    pub fn is_transient(&self) -> bool {
+        matches!(self.kind(), ErrorKind::Protocol | ErrorKind::Timeout)
+    }
+
+    /// Classifies an error returned when opening a device that was found.
+    #[cfg(feature = "hid")]
+    pub(crate) fn from_open(err: HidError) -> Self {
+        match err {
+            HidError::HidApiError { ref message } if is_permission_error(message) => {
+                Error::PermissionDenied
+            }
+            err => err.into(),
+        }
+    }
+
+    /// Classifies an error returned when using an open device.
+    #[cfg(feature = "hid")]
+    pub(crate) fn from_transfer(err: HidError) -> Self {
+        match err {
+            HidError::HidApiError { ref message } if is_disconnect_error(message) => {
+                Error::Disconnected
+            }
+            err => err.into(),
+        }
+    }
+}
+
+/// Returns whether a `hidapi` error message says that the device could not
+/// be opened for lack of permissions.
+///
+/// `hidapi` only passes on the messages of the backends, like the `strerror`
+/// text on Linux or the `FormatMessage` one on Windows, which is all there is
+/// to go by.
+#[cfg(feature = "hid")]
+fn is_permission_error(message: &str) -> bool {
+    let message = message.to_ascii_lowercase();
+    ["permission denied", "access denied", "access is denied"]
+        .iter()
+        .any(|text| message.contains(text))
+}
+
+/// Returns whether a `hidapi` error message says that the device is gone.
+#[cfg(feature = "hid")]
+fn is_disconnect_error(message: &str) -> bool {
+    let message = message.to_ascii_lowercase();
+    [
+        "no such device",
+        "device not configured",
+        "not connected",
+        "disconnected",
+    ]
+    .iter()
+    .any(|text| message.contains(text))
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
 impl From<zg_co2::Error> for Error {
     fn from(err: zg_co2::Error) -> Self {
         match err {
@@ -45,17 +207,75 @@ impl Display for Error {
         match self {
             Error::InvalidMessage => write!(f, "invalid message"),
             Error::Checksum => write!(f, "checksum error"),
+            #[cfg(feature = "hid")]
             Error::Hid(err) => err.fmt(f),
+            Error::Io(err) => err.fmt(f),
             Error::Timeout => write!(f, "timeout"),
             Error::InvalidTimeout => write!(f, "invalid timeout"),
+            Error::InvalidSerialNumber => write!(f, "invalid serial number"),
+            Error::InvalidPath => write!(f, "invalid device path"),
+            Error::InvalidProductIds => write!(f, "invalid product IDs"),
+            Error::InvalidUsbLocation => write!(f, "invalid USB location"),
+            Error::InvalidEnvVar(name) => write!(f, "invalid value for {}", name),
+            Error::Unsupported => write!(f, "operation not supported"),
+            Error::DeviceNotFound => write!(f, "device not found"),
+            Error::PermissionDenied => write!(f, "permission denied"),
+            Error::Disconnected => write!(f, "device disconnected"),
         }
     }
 }
 
+/// The error returned by a batch read like [`Sensor::read_n`], along with
+/// the readings taken before it.
+///
+/// [`Sensor::read_n`]: crate::Sensor::read_n
+#[derive(Debug)]
+pub struct PartialRead<T> {
+    pub(crate) readings: Vec<T>,
+    pub(crate) error: Error,
+}
+
+impl<T> PartialRead<T> {
+    /// Returns the readings taken before the error.
+    pub fn readings(&self) -> &[T] {
+        &self.readings
+    }
+
+    /// Returns the error which stopped the batch.
+    pub fn error(&self) -> &Error {
+        &self.error
+    }
+
+    /// Returns the readings and the error.
+    pub fn into_parts(self) -> (Vec<T>, Error) {
+        (self.readings, self.error)
+    }
+}
+
+impl<T> From<PartialRead<T>> for Error {
+    fn from(err: PartialRead<T>) -> Self {
+        err.error
+    }
+}
+
+impl<T> Display for PartialRead<T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{} after {} readings", self.error, self.readings.len())
+    }
+}
+
+impl<T: fmt::Debug> error::Error for PartialRead<T> {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
 impl error::Error for Error {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match self {
+            #[cfg(feature = "hid")]
             Error::Hid(cause) => Some(cause.as_ref()),
+            Error::Io(cause) => Some(cause),
             _ => None,
         }
     }
@@ -63,6 +283,60 @@ impl error::Error for Error {
 
 #[cfg(test)]
 mod tests {
+    use super::{Error, ErrorKind};
+    use std::io;
+
+    #[test]
+    fn test_kind() {
+        let err = Error::from(io::Error::from(io::ErrorKind::BrokenPipe));
+        assert_eq!(err.kind(), ErrorKind::Connectivity);
+        assert!(!err.is_transient());
+        assert_eq!(Error::Disconnected.kind(), ErrorKind::Connectivity);
+        assert_eq!(Error::Checksum.kind(), ErrorKind::Protocol);
+        assert!(Error::Checksum.is_transient());
+        assert!(Error::Timeout.is_transient());
+        assert_eq!(Error::InvalidPath.kind(), ErrorKind::Configuration);
+        assert!(!Error::Unsupported.is_transient());
+    }
+
+    #[cfg(feature = "hid")]
+    #[test]
+    fn test_classify() {
+        use hidapi::HidError;
+
+        let error = |message: &str| HidError::HidApiError {
+            message: message.into(),
+        };
+        assert!(matches!(
+            Error::from_open(error("Failed to open /dev/hidraw0: Permission denied")),
+            Error::PermissionDenied
+        ));
+        assert!(matches!(
+            Error::from_open(error("Access is denied.")),
+            Error::PermissionDenied
+        ));
+        assert!(matches!(
+            Error::from_open(HidError::HidApiErrorEmpty),
+            Error::Hid(_)
+        ));
+        assert!(matches!(
+            Error::from_transfer(error("Failed to read: No such device")),
+            Error::Disconnected
+        ));
+        assert!(matches!(
+            Error::from_transfer(error("The device is not connected.")),
+            Error::Disconnected
+        ));
+        assert!(matches!(
+            Error::from_transfer(error("Resource temporarily unavailable")),
+            Error::Hid(_)
+        ));
+        assert!(matches!(
+            Error::from_transfer(HidError::HidApiErrorEmpty),
+            Error::Hid(_)
+        ));
+    }
+
     #[test]
     fn test_send() {
         fn assert_send<T: Send>() {}
@@ -0,0 +1,190 @@
+//! Reading from a backup sensor when the primary one fails.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use co2mon::failover::FailoverSensor;
+//! # use co2mon::{OpenOptions, Result};
+//! # #[cfg(feature = "hid")]
+//! # fn main() -> Result<()> {
+//! #
+//! let sensor = FailoverSensor::new(vec![
+//!     OpenOptions::new().with_serial_number("1.40").open()?,
+//!     OpenOptions::new().with_serial_number("1.41").open()?,
+//! ])?;
+//! let (index, reading) = sensor.read()?;
+//! println!("sensor {}: {} ppm CO₂", index, reading.co2());
+//! #
+//! # Ok(())
+//! # }
+//! # #[cfg(not(feature = "hid"))]
+//! # fn main() {}
+//! ```
+
+use crate::{Error, Reading, Result, Sensor, SingleReading};
+use std::cell::Cell;
+
+/// A group of sensors, of which only one is used at a time.
+///
+/// The first sensor is used until it times out or is disconnected, after
+/// which the next one takes over. The active sensor only changes on failure,
+/// so once the primary sensor has recovered, it is not used again until the
+/// others have failed too.
+///
+/// Other errors, like bad checksums or malformed reports, are returned without
+/// switching.
+pub struct FailoverSensor {
+    sensors: Vec<Sensor>,
+    active: Cell<usize>,
+}
+
+impl FailoverSensor {
+    /// Creates a group of sensors, in order of preference.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::DeviceNotFound`] will be returned if `sensors` is empty.
+    pub fn new(sensors: Vec<Sensor>) -> Result<Self> {
+        if sensors.is_empty() {
+            return Err(Error::DeviceNotFound);
+        }
+        Ok(Self {
+            sensors,
+            active: Cell::new(0),
+        })
+    }
+
+    /// Returns the sensors, in order of preference.
+    pub fn sensors(&self) -> &[Sensor] {
+        &self.sensors
+    }
+
+    /// Returns the index of the sensor that will be used for the next read.
+    pub fn active(&self) -> usize {
+        self.active.get()
+    }
+
+    /// Takes a single reading using [`Sensor::read_one_until`], with a
+    /// deadline of the sensor's timeout from now, and returns it along with
+    /// the index of the sensor that provided it.
+    ///
+    /// # Errors
+    ///
+    /// The error of the last sensor will be returned if all of them failed.
+    /// Other errors are returned as they are.
+    pub fn read_one(&self) -> Result<(usize, SingleReading)> {
+        self.read_with(|sensor| {
+            let deadline = sensor
+                .timeout()
+                .and_then(|timeout| sensor.deadline_after(timeout));
+            sensor.read_one_by(deadline)
+        })
+    }
+
+    /// Takes a reading using [`Sensor::read`], and returns it along with the
+    /// index of the sensor that provided it.
+    ///
+    /// # Errors
+    ///
+    /// The error of the last sensor will be returned if all of them failed.
+    /// Other errors are returned as they are.
+    pub fn read(&self) -> Result<(usize, Reading)> {
+        self.read_with(Sensor::read)
+    }
+
+    fn read_with<T, F>(&self, read: F) -> Result<(usize, T)>
+    where
+        F: Fn(&Sensor) -> Result<T>,
+    {
+        let first = self.active.get();
+        let mut index = first;
+        loop {
+            match read(&self.sensors[index]) {
+                Ok(val) => return Ok((index, val)),
+                Err(e) if is_failure(&e) => {
+                    index = (index + 1) % self.sensors.len();
+                    self.active.set(index);
+                    if index == first {
+                        return Err(e);
+                    }
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+fn is_failure(error: &Error) -> bool {
+    match error {
+        #[cfg(feature = "hid")]
+        Error::Hid(_) => true,
+        Error::Io(_) | Error::Disconnected | Error::Timeout => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FailoverSensor;
+    use crate::fixtures::CO2;
+    use crate::mock::{Fault, MockBackend};
+    use crate::{Error, OpenOptions, SingleReading};
+
+    #[test]
+    fn test_failover() {
+        let primary = MockBackend::new(vec![CO2]).disconnect_after_reads(1);
+        let backup = MockBackend::new(vec![CO2]).fail_every(2, Fault::Checksum);
+        let sensor = FailoverSensor::new(vec![
+            OpenOptions::new().open_backend(primary).unwrap(),
+            OpenOptions::new().open_backend(backup).unwrap(),
+        ])
+        .unwrap();
+
+        assert_eq!(sensor.read_one().unwrap(), (0, SingleReading::CO2(1111)));
+        assert_eq!(sensor.read_one().unwrap(), (1, SingleReading::CO2(1111)));
+        assert_eq!(sensor.active(), 1);
+        // no failover on checksum errors
+        assert!(matches!(sensor.read_one(), Err(Error::Checksum)));
+        assert_eq!(sensor.active(), 1);
+    }
+
+    #[test]
+    fn test_malformed_report() {
+        let primary = MockBackend::new(vec![CO2]).fail_every(2, Fault::ShortRead);
+        let backup = MockBackend::new(vec![CO2]);
+        let sensor = FailoverSensor::new(vec![
+            OpenOptions::new().open_backend(primary).unwrap(),
+            OpenOptions::new().open_backend(backup).unwrap(),
+        ])
+        .unwrap();
+
+        assert_eq!(sensor.read_one().unwrap(), (0, SingleReading::CO2(1111)));
+        // a short read is reported, but doesn't switch sensors
+        assert!(matches!(sensor.read_one(), Err(Error::InvalidMessage)));
+        assert_eq!(sensor.active(), 0);
+        assert_eq!(sensor.read_one().unwrap(), (0, SingleReading::CO2(1111)));
+    }
+
+    #[test]
+    fn test_all_failed() {
+        let sensor = FailoverSensor::new(vec![
+            OpenOptions::new()
+                .open_backend(MockBackend::new(vec![CO2]).disconnect_after_reads(0))
+                .unwrap(),
+            OpenOptions::new()
+                .open_backend(MockBackend::new(Vec::new()))
+                .unwrap(),
+        ])
+        .unwrap();
+        assert!(matches!(sensor.read_one(), Err(Error::Timeout)));
+        assert_eq!(sensor.active(), 0);
+    }
+
+    #[test]
+    fn test_empty() {
+        assert!(matches!(
+            FailoverSensor::new(Vec::new()),
+            Err(Error::DeviceNotFound)
+        ));
+    }
+}
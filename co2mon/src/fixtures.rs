@@ -0,0 +1,40 @@
+//! Reports and helpers shared by the unit tests.
+
+use crate::Clock;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// An unencrypted report with a CO₂ concentration of 1111 ppm.
+pub(crate) const CO2: [u8; 8] = [0x50, 0x04, 0x57, 0xab, 0x0d, 0x00, 0x00, 0x00];
+/// An unencrypted report with a temperature of 21.4125 °C.
+pub(crate) const TEMPERATURE: [u8; 8] = [0x42, 0x12, 0x69, 0xbd, 0x0d, 0x00, 0x00, 0x00];
+
+/// A clock which advances by a fixed step every time it is read, and
+/// doesn't block when sleeping.
+pub(crate) struct FakeClock {
+    start: Instant,
+    elapsed: Mutex<Duration>,
+    step: Duration,
+}
+
+impl Clock for FakeClock {
+    fn now(&self) -> Instant {
+        let mut elapsed = self.elapsed.lock().unwrap();
+        let now = self.start + *elapsed;
+        *elapsed += self.step;
+        now
+    }
+
+    #[cfg(feature = "hid")]
+    fn sleep(&self, duration: Duration) {
+        *self.elapsed.lock().unwrap() += duration;
+    }
+}
+
+pub(crate) fn fake_clock(step: Duration) -> Box<dyn Clock> {
+    Box::new(FakeClock {
+        start: Instant::now(),
+        elapsed: Mutex::new(Duration::ZERO),
+        step,
+    })
+}
@@ -0,0 +1,36 @@
+use crate::{Reading, Result, Sensor, SingleReading};
+use std::iter::FusedIterator;
+
+/// An endless iterator over the readings of a sensor.
+///
+/// See [`Sensor::readings`].
+pub struct Readings<'a> {
+    pub(crate) sensor: &'a Sensor,
+}
+
+impl Iterator for Readings<'_> {
+    type Item = Result<Reading>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.sensor.read())
+    }
+}
+
+impl FusedIterator for Readings<'_> {}
+
+/// An endless iterator over the single readings of a sensor.
+///
+/// See [`Sensor::single_readings`].
+pub struct SingleReadings<'a> {
+    pub(crate) sensor: &'a Sensor,
+}
+
+impl Iterator for SingleReadings<'_> {
+    type Item = Result<SingleReading>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.sensor.read_one())
+    }
+}
+
+impl FusedIterator for SingleReadings<'_> {}
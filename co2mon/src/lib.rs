@@ -13,6 +13,7 @@
 //!
 //! ```no_run
 //! # use co2mon::{Result, Sensor};
+//! # #[cfg(feature = "hid")]
 //! # fn main() -> Result<()> {
 //! #
 //! let sensor = Sensor::open_default()?;
@@ -21,6 +22,8 @@
 //! #
 //! # Ok(())
 //! # }
+//! # #[cfg(not(feature = "hid"))]
+//! # fn main() {}
 //! ```
 //!
 //! # Permissions
@@ -41,23 +44,122 @@
 //!
 //! Note that the `udev` rule above makes the device accessible to every local user.
 //!
+//! # Platform support
+//!
+//! The device is accessed through [`hidapi`][hidapi]. On Linux, the `linux-*`
+//! features select the `hidapi` backend (`libusb` or `hidraw`, linked
+//! statically or dynamically), with `linux-static-libusb` being the default.
+//!
+//! On FreeBSD and OpenBSD, which have no `hidraw`, the system `hidapi` library
+//! (built on top of `libusb`) is used instead and the `linux-*` features have
+//! no effect. Install it from the `comms/hidapi` port or package before building.
+//!
+//! `hidapi` only allows one context per process, so the sensors share a
+//! single one, which is created when the first device is opened or listed
+//! and kept until the process exits. Applications that also use `hidapi` for
+//! other devices will fail to create their own context after that.
+//!
+//! # Features
+//!
+//!  - `hid`: the USB HID driver, enabled by each of the `linux-*` features.
+//!    Without it, `hidapi` is not built and only [`decode_report`],
+//!    [`OpenOptions::open_stream`] and [`OpenOptions::open_backend`] are
+//!    available, which makes the crate usable on targets like `wasm32-wasi`
+//!    to process reports that were captured or forwarded from elsewhere.
+//!  - `linux-static-libusb` (default), `linux-static-hidraw`,
+//!    `linux-shared-libusb`, `linux-shared-hidraw`: the `hidapi` backend to
+//!    use on Linux, see above. Only one of them should be enabled.
+//!
+//! On other platforms, builds with `default-features = false` have to enable
+//! `hid` explicitly to keep the driver, since the `linux-*` features are the
+//! only ones that pull it in:
+//!
+//! ```toml
+//! [dependencies]
+//! co2mon = { version = "2", default-features = false, features = ["hid"] }
+//! ```
+//!
+//! Everything else (the [`payload`], [`merge`] and [`calibration`] modules,
+//! for example) is plain Rust code without dependencies, and is always
+//! available.
+//!
+//! On embedded Linux systems, the smallest build of the driver links to the
+//! system `hidapi` library using `hidraw`, which avoids building `libusb`:
+//!
+//! ```toml
+//! [dependencies]
+//! co2mon = { version = "2", default-features = false, features = ["linux-shared-hidraw"] }
+//! ```
+//!
 //! # References
 //!
 //! The USB HID protocol is not documented, but was [reverse-engineered][had] [before][revspace].
 //!
 //! [had]: https://hackaday.io/project/5301/
+//! [hidapi]: https://docs.rs/hidapi/
 //! [revspace]: https://revspace.nl/CO2MeterHacking
 
-use hidapi::{HidApi, HidDevice};
+use backend::StreamBackend;
+use clock::{Clock, SystemClock};
+#[cfg(feature = "hid")]
+use hidapi::HidApi;
+use std::cell::{Cell, RefCell};
+use std::collections::hash_map::RandomState;
 use std::convert::TryFrom;
+use std::env;
+#[cfg(feature = "hid")]
 use std::ffi::CString;
+use std::ffi::OsString;
+use std::fmt::{self, Display, Formatter};
+#[cfg(unix)]
+use std::fs::File;
+use std::hash::{BuildHasher, Hasher};
+use std::io::Read;
+use std::mem;
+use std::panic;
 use std::result;
-use std::time::{Duration, Instant};
+#[cfg(feature = "hid")]
+use std::sync::{Mutex, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-pub use error::Error;
+pub use backend::Backend;
+pub use capabilities::Capabilities;
+#[cfg(feature = "hid")]
+pub use device_info::DeviceInfo;
+pub use error::{Error, ErrorKind, PartialRead};
+pub use iter::{Readings, SingleReadings};
+#[cfg(unix)]
+pub use poller::FdReceiver;
+pub use poller::Poller;
+#[cfg(feature = "hid")]
+pub use reconnect::ReconnectPolicy;
+pub use shared::SharedSensor;
+pub use stats::Stats;
+pub use timestamped::Timestamped;
 pub use zg_co2::SingleReading;
 
+pub mod adapters;
+mod backend;
+pub mod calibration;
+mod capabilities;
+mod clock;
+#[cfg(feature = "hid")]
+mod device_info;
 mod error;
+pub mod failover;
+#[cfg(test)]
+mod fixtures;
+mod iter;
+pub mod merge;
+pub mod mock;
+pub mod payload;
+mod poller;
+#[cfg(feature = "hid")]
+mod reconnect;
+mod shared;
+mod stats;
+mod timestamped;
 
 /// A specialized [`Result`][std::result::Result] type for the fallible functions.
 pub type Result<T> = result::Result<T, Error>;
@@ -68,6 +170,7 @@ pub type Result<T> = result::Result<T, Error>;
 ///
 /// ```no_run
 /// # use co2mon::{Result, Sensor};
+/// # #[cfg(feature = "hid")]
 /// # fn main() -> Result<()> {
 /// #
 /// let sensor = Sensor::open_default()?;
@@ -76,6 +179,8 @@ pub type Result<T> = result::Result<T, Error>;
 /// #
 /// # Ok(())
 /// # }
+/// # #[cfg(not(feature = "hid"))]
+/// # fn main() {}
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub struct Reading {
     temperature: f32,
@@ -83,12 +188,41 @@ pub struct Reading {
 }
 
 impl Reading {
+    /// Creates a reading from a temperature (in °C) and a CO₂ concentration
+    /// (in ppm), e.g. to use as a test fixture.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use co2mon::Reading;
+    /// let reading = Reading::new(21.5, 950);
+    /// assert_eq!(reading.co2(), 950);
+    /// ```
+    pub fn new(temperature: f32, co2: u16) -> Self {
+        Self { temperature, co2 }
+    }
+
+    /// Returns the temperature (in °C) and the CO₂ concentration (in ppm).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use co2mon::Reading;
+    /// let (temperature, co2) = Reading::new(21.5, 950).into_parts();
+    /// assert_eq!(temperature, 21.5);
+    /// assert_eq!(co2, 950);
+    /// ```
+    pub fn into_parts(self) -> (f32, u16) {
+        (self.temperature, self.co2)
+    }
+
     /// Returns the measured temperature in °C.
     ///
     /// # Example
     ///
     /// ```no_run
     /// # use co2mon::{Result, Sensor};
+    /// # #[cfg(feature = "hid")]
     /// # fn main() -> Result<()> {
     /// #
     /// let sensor = Sensor::open_default()?;
@@ -97,374 +231,2362 @@ impl Reading {
     /// #
     /// # Ok(())
     /// # }
+    /// # #[cfg(not(feature = "hid"))]
+    /// # fn main() {}
     pub fn temperature(&self) -> f32 {
         self.temperature
     }
 
-    /// Returns the CO₂ concentration in ppm (parts per million).
+    /// Returns the measured temperature in °F.
     ///
     /// # Example
     ///
     /// ```no_run
     /// # use co2mon::{Result, Sensor};
+    /// # #[cfg(feature = "hid")]
     /// # fn main() -> Result<()> {
     /// #
     /// let sensor = Sensor::open_default()?;
     /// let reading = sensor.read()?;
-    /// println!("{} ppm CO₂", reading.co2());
+    /// println!("{} °F", reading.temperature_fahrenheit());
     /// #
     /// # Ok(())
     /// # }
-    pub fn co2(&self) -> u16 {
-        self.co2
+    /// # #[cfg(not(feature = "hid"))]
+    /// # fn main() {}
+    pub fn temperature_fahrenheit(&self) -> f32 {
+        self.temperature * 1.8 + 32.0
     }
-}
-
-/// Sensor driver struct.
-///
-/// # Example
-///
-/// ```no_run
-/// # use co2mon::{Result, Sensor};
-/// # fn main() -> Result<()> {
-/// #
-/// let sensor = Sensor::open_default()?;
-/// let reading = sensor.read_one()?;
-/// println!("{:?}", reading);
-/// #
-/// # Ok(())
-/// # }
-/// ```
-pub struct Sensor {
-    device: HidDevice,
-    key: [u8; 8],
-    timeout: i32,
-}
 
-impl Sensor {
-    /// Opens the sensor device using the default USB Vendor ID (`0x04d9`) and Product ID (`0xa052`) values.
-    ///
-    /// When multiple devices are connected, the first one will be used.
+    /// Returns the measured temperature in K.
     ///
     /// # Example
     ///
     /// ```no_run
     /// # use co2mon::{Result, Sensor};
+    /// # #[cfg(feature = "hid")]
     /// # fn main() -> Result<()> {
     /// #
     /// let sensor = Sensor::open_default()?;
-    /// let reading = sensor.read_one()?;
-    /// println!("{:?}", reading);
+    /// let reading = sensor.read()?;
+    /// println!("{} K", reading.temperature_kelvin());
     /// #
     /// # Ok(())
     /// # }
-    pub fn open_default() -> Result<Self> {
-        OpenOptions::new().open()
-    }
-
-    fn open(options: &OpenOptions) -> Result<Self> {
-        let hidapi = HidApi::new()?;
-
-        const VID: u16 = 0x04d9;
-        const PID: u16 = 0xa052;
-
-        let device = match options.path_type {
-            DevicePathType::Id => hidapi.open(VID, PID),
-            DevicePathType::SerialNumber(ref sn) => hidapi.open_serial(VID, PID, sn),
-            DevicePathType::Path(ref path) => hidapi.open_path(path),
-        }?;
-
-        let key = options.key;
-
-        // fill in the Report Id
-        let frame = {
-            let mut frame = [0; 9];
-            frame[1..9].copy_from_slice(&key);
-            frame
-        };
-        device.send_feature_report(&frame)?;
-
-        let timeout = options
-            .timeout
-            .map(|timeout| timeout.as_millis())
-            .map_or(Ok(-1), i32::try_from)
-            .map_err(|_| Error::InvalidTimeout)?;
-
-        let air_control = Self {
-            device,
-            key,
-            timeout,
-        };
-        Ok(air_control)
+    /// # #[cfg(not(feature = "hid"))]
+    /// # fn main() {}
+    pub fn temperature_kelvin(&self) -> f32 {
+        self.temperature + 273.15
     }
 
-    /// Takes a single reading from the sensor.
-    ///
-    /// # Errors
+    /// Returns the measured temperature as sent by the device, in 1/16 K.
     ///
-    /// An error will be returned on an I/O error or if a message could not be
-    /// read or decoded.
+    /// The reading only stores the temperature in °C, so this converts it
+    /// back using [`zg_co2::celsius_to_ticks`]. For readings taken from the
+    /// device, the result is exact.
     ///
     /// # Example
     ///
     /// ```no_run
     /// # use co2mon::{Result, Sensor};
+    /// # #[cfg(feature = "hid")]
     /// # fn main() -> Result<()> {
     /// #
     /// let sensor = Sensor::open_default()?;
-    /// let reading = sensor.read_one()?;
-    /// println!("{:?}", reading);
+    /// let reading = sensor.read()?;
+    /// println!("{} / 16 K", reading.temperature_raw());
     /// #
     /// # Ok(())
     /// # }
-    pub fn read_one(&self) -> Result<SingleReading> {
-        let mut data = [0; 8];
-        if self.device.read_timeout(&mut data, self.timeout)? != 8 {
-            return Err(Error::InvalidMessage);
-        }
-
-        // if the "magic byte" is present no decryption is necessary. This is the case for AIRCO2NTROL COACH
-        // and newer AIRCO2NTROL MINIs in general
-        let data = if data[4] == 0x0d {
-            data
-        } else {
-            decrypt(data, self.key)
-        };
-        let reading = zg_co2::decode([data[0], data[1], data[2], data[3], data[4]])?;
-        Ok(reading)
+    /// # #[cfg(not(feature = "hid"))]
+    /// # fn main() {}
+    pub fn temperature_raw(&self) -> u16 {
+        zg_co2::celsius_to_ticks(self.temperature)
     }
 
-    /// Takes a multiple readings from the sensor until the temperature and
-    /// CO₂ concentration are available, and returns both.
-    ///
-    /// # Errors
-    ///
-    /// An error will be returned on an I/O error or if a message could not be
-    /// read or decoded.
+    /// Returns the CO₂ concentration in ppm (parts per million).
     ///
     /// # Example
     ///
     /// ```no_run
     /// # use co2mon::{Result, Sensor};
+    /// # #[cfg(feature = "hid")]
     /// # fn main() -> Result<()> {
     /// #
     /// let sensor = Sensor::open_default()?;
     /// let reading = sensor.read()?;
-    /// println!("{} °C, {} ppm CO₂", reading.temperature(), reading.co2());
+    /// println!("{} ppm CO₂", reading.co2());
     /// #
     /// # Ok(())
     /// # }
-    pub fn read(&self) -> Result<Reading> {
-        let start = Instant::now();
+    /// # #[cfg(not(feature = "hid"))]
+    /// # fn main() {}
+    pub fn co2(&self) -> u16 {
+        self.co2
+    }
+}
+
+impl Display for Reading {
+    /// Formats the reading for display, e.g. `21.4 °C, 950 ppm CO₂`.
+    ///
+    /// The alternate form (`{:#}`) is meant for logs and other programs, and
+    /// keeps the full precision, e.g. `temperature=21.4125 co2=950`.
+    ///
+    /// In both forms, the precision sets the number of decimals of the
+    /// temperature, so `{:#.2}` gives `temperature=21.41 co2=950`.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match (f.alternate(), f.precision()) {
+            (true, Some(precision)) => write!(
+                f,
+                "temperature={:.*} co2={}",
+                precision, self.temperature, self.co2
+            ),
+            (true, None) => write!(f, "temperature={} co2={}", self.temperature, self.co2),
+            (false, precision) => write!(
+                f,
+                "{:.*} °C, {} ppm CO₂",
+                precision.unwrap_or(1),
+                self.temperature,
+                self.co2
+            ),
+        }
+    }
+}
+
+impl TryFrom<&[SingleReading]> for Reading {
+    type Error = Error;
+
+    /// Combines the latest temperature and CO₂ concentration out of a list of
+    /// single readings, e.g. ones collected by [`Sensor::read_one`].
+    ///
+    /// # Errors
+    ///
+    /// [`Error::InvalidMessage`] will be returned if either value is missing.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use co2mon::{Reading, SingleReading};
+    /// # use std::convert::TryFrom;
+    /// let readings = [SingleReading::CO2(1111), SingleReading::Temperature(21.5)];
+    /// let reading = Reading::try_from(&readings[..]).unwrap();
+    /// assert_eq!(reading.co2(), 1111);
+    /// ```
+    fn try_from(readings: &[SingleReading]) -> Result<Self> {
         let mut temperature = None;
         let mut co2 = None;
-        loop {
-            let reading = self.read_one()?;
-            match reading {
+        for reading in readings {
+            match *reading {
                 SingleReading::Temperature(val) => temperature = Some(val),
                 SingleReading::CO2(val) => co2 = Some(val),
                 _ => {}
             }
-            if let (Some(temperature), Some(co2)) = (temperature, co2) {
-                let reading = Reading { temperature, co2 };
-                return Ok(reading);
-            }
-
-            if self.timeout != -1 {
-                let duration = Instant::now() - start;
-                if duration.as_millis() > self.timeout as u128 {
-                    return Err(Error::Timeout);
-                }
-            }
+        }
+        match (temperature, co2) {
+            (Some(temperature), Some(co2)) => Ok(Reading { temperature, co2 }),
+            _ => Err(Error::InvalidMessage),
         }
     }
 }
 
-fn decrypt(mut data: [u8; 8], key: [u8; 8]) -> [u8; 8] {
-    data.swap(0, 2);
-    data.swap(1, 4);
-    data.swap(3, 7);
-    data.swap(5, 6);
+/// All the values reported by the sensor during a reporting cycle.
+///
+/// See [`Sensor::read_all`].
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct FullReading {
+    reading: Reading,
+    humidity: Option<f32>,
+    other: Vec<(u8, u16)>,
+}
 
-    for (r, k) in data.iter_mut().zip(key.iter()) {
-        *r ^= k;
+impl FullReading {
+    /// Returns the measured temperature in °C.
+    pub fn temperature(&self) -> f32 {
+        self.reading.temperature
     }
 
-    let tmp = data[7] << 5;
-    data[7] = data[6] << 5 | data[7] >> 3;
-    data[6] = data[5] << 5 | data[6] >> 3;
-    data[5] = data[4] << 5 | data[5] >> 3;
-    data[4] = data[3] << 5 | data[4] >> 3;
-    data[3] = data[2] << 5 | data[3] >> 3;
-    data[2] = data[1] << 5 | data[2] >> 3;
-    data[1] = data[0] << 5 | data[1] >> 3;
-    data[0] = tmp | data[0] >> 3;
+    /// Returns the CO₂ concentration in ppm (parts per million).
+    pub fn co2(&self) -> u16 {
+        self.reading.co2
+    }
 
-    for (r, m) in data.iter_mut().zip(b"Htemp99e".iter()) {
-        *r = r.wrapping_sub(m << 4 | m >> 4);
+    /// Returns the relative humidity in %, if the device measures it.
+    ///
+    /// See [`Sensor::humidity_supported`].
+    pub fn humidity(&self) -> Option<f32> {
+        self.humidity
     }
 
-    data
-}
+    /// Returns the readings of unknown kinds, as pairs of opcodes and raw
+    /// values.
+    pub fn other(&self) -> &[(u8, u16)] {
+        &self.other
+    }
 
-#[derive(Debug, Clone)]
-enum DevicePathType {
-    Id,
-    SerialNumber(String),
-    Path(CString),
+    /// Returns the temperature and CO₂ concentration.
+    pub fn reading(&self) -> &Reading {
+        &self.reading
+    }
 }
 
-/// Sensor open options.
-///
-/// Opens the first available device with the USB Vendor ID `0x04d9`
-/// and Product ID `0xa052`, a `0` encryption key and a 5 seconds timeout.
+/// Sensor driver struct.
 ///
-/// Normally there's no need to change the encryption key.
+/// A sensor can be moved to another thread, but not shared between threads.
+/// See [`SharedSensor`] and [`Poller`] for ways to do that.
 ///
 /// # Example
 ///
 /// ```no_run
-/// # use co2mon::{OpenOptions, Result};
-/// # use std::time::Duration;
+/// # use co2mon::{Result, Sensor};
+/// # #[cfg(feature = "hid")]
 /// # fn main() -> Result<()> {
 /// #
-/// let sensor = OpenOptions::new()
-///     .timeout(Some(Duration::from_secs(10)))
-///     .open()?;
+/// let sensor = Sensor::open_default()?;
+/// let reading = sensor.read_one()?;
+/// println!("{:?}", reading);
 /// #
 /// # Ok(())
 /// # }
-#[derive(Debug, Clone)]
-pub struct OpenOptions {
-    path_type: DevicePathType,
-    key: [u8; 8],
-    timeout: Option<Duration>,
+/// # #[cfg(not(feature = "hid"))]
+/// # fn main() {}
+/// ```
+pub struct Sensor {
+    device: RefCell<Box<dyn Backend>>,
+    key: Cell<[u8; 8]>,
+    timeout: i32,
+    clock: Box<dyn Clock>,
+    stats: Cell<Stats>,
+    // the number of zero humidity readings, or `None` after a non-zero one
+    zero_humidity: Cell<Option<u32>>,
+    seen: Cell<Capabilities>,
+    read_strategy: ReadStrategy,
+    latest_temperature: Cell<Option<f32>>,
+    latest_co2: Cell<Option<u16>>,
+    // the reading which ended the last cycle in `read_all`, and starts the next one
+    pending: Cell<Option<SingleReading>>,
+    // when the last report was received
+    received: Cell<Option<(Instant, SystemTime)>>,
+    reinitialize_after: Option<u32>,
+    consecutive_timeouts: Cell<u32>,
+    #[cfg(feature = "hid")]
+    info: RefCell<Option<DeviceInfo>>,
+    #[cfg(feature = "hid")]
+    reopen: Option<Reopen>,
+    #[cfg(feature = "hid")]
+    auto_reconnect: Option<ReconnectPolicy>,
 }
 
-impl Default for OpenOptions {
-    fn default() -> Self {
-        Self::new()
-    }
-}
+/// Opens the device again, finding it in the same way as the first time.
+#[cfg(feature = "hid")]
+type Reopen = Box<dyn Fn() -> Result<(Box<dyn Backend>, Option<DeviceInfo>)> + Send>;
 
-impl OpenOptions {
-    /// Creates a new set of options to be configured.
+#[cfg(feature = "hid")]
+const VID: u16 = 0x04d9;
+/// The Product IDs of the known devices, in the order in which they are
+/// tried. More can be passed to [`OpenOptions::with_product_ids`].
+#[cfg(feature = "hid")]
+const PRODUCT_IDS: &[u16] = &[0xa052];
+
+/// The number of zero humidity readings after which the humidity is assumed
+/// to be unsupported.
+const ZERO_HUMIDITY_READINGS: u32 = 5;
+
+impl Sensor {
+    /// Opens the sensor device using the default USB Vendor ID (`0x04d9`) and
+    /// the known Product IDs (currently only `0xa052`).
     ///
-    /// The defaults are opening the first connected sensor and a timeout of
-    /// 5 seconds.
+    /// When multiple devices are connected, the first one will be used,
+    /// preferring the Product IDs in order. See [`Sensor::product_id`] for
+    /// the one that was matched.
     ///
     /// # Example
     ///
     /// ```no_run
-    /// # use co2mon::{OpenOptions, Result};
-    /// # use std::time::Duration;
+    /// # use co2mon::{Result, Sensor};
     /// # fn main() -> Result<()> {
     /// #
-    /// let sensor = OpenOptions::new()
-    ///     .timeout(Some(Duration::from_secs(10)))
-    ///     .open()?;
+    /// let sensor = Sensor::open_default()?;
+    /// let reading = sensor.read_one()?;
+    /// println!("{:?}", reading);
     /// #
     /// # Ok(())
     /// # }
-    pub fn new() -> Self {
-        Self {
-            path_type: DevicePathType::Id,
-            key: [0; 8],
-            timeout: Some(Duration::from_secs(5)),
-        }
+    #[cfg(feature = "hid")]
+    pub fn open_default() -> Result<Self> {
+        OpenOptions::new().open()
     }
 
-    /// Sets the serial number of the sensor device to open.
+    /// Lists the connected sensor devices.
     ///
-    /// The serial number appears to be the firmware version.
+    /// # Errors
+    ///
+    /// An error will be returned if the USB devices could not be listed.
     ///
     /// # Example
     ///
     /// ```no_run
-    /// # use co2mon::OpenOptions;
-    /// # use std::error::Error;
-    /// # use std::ffi::CString;
-    /// # use std::result::Result;
-    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// # use co2mon::{OpenOptions, Result, Sensor};
+    /// # fn main() -> Result<()> {
     /// #
-    /// let sensor = OpenOptions::new()
-    ///     .with_serial_number("1.40")
-    ///     .open()?;
+    /// for info in Sensor::enumerate()? {
+    ///     println!("{:?}: {:?}", info.path(), info.serial_number());
+    ///     let sensor = OpenOptions::new().with_path(info.path().clone()).open()?;
+    /// }
     /// #
     /// # Ok(())
     /// # }
-    pub fn with_serial_number<S: Into<String>>(&mut self, sn: S) -> &mut Self {
-        self.path_type = DevicePathType::SerialNumber(sn.into());
-        self
+    #[cfg(feature = "hid")]
+    pub fn enumerate() -> Result<Vec<DeviceInfo>> {
+        let options = OpenOptions::new();
+        with_hidapi(|hidapi| {
+            let devices = hidapi
+                .device_list()
+                .filter(|info| options.matches(info))
+                .map(DeviceInfo::from_hid)
+                .collect();
+            Ok(devices)
+        })
     }
 
-    /// Sets the path to the sensor device to open.
+    #[cfg(feature = "hid")]
+    fn open(options: &OpenOptions) -> Result<Self> {
+        options.validate()?;
+        let (device, info) = Self::open_device(options)?;
+
+        let mut sensor = Self::with_backend(device, options)?;
+        sensor.info = RefCell::new(info);
+        let options = options.clone();
+        sensor.reopen = Some(Box::new(move || Self::open_device(&options)));
+        Ok(sensor)
+    }
+
+    #[cfg(feature = "hid")]
+    fn open_device(options: &OpenOptions) -> Result<(Box<dyn Backend>, Option<DeviceInfo>)> {
+        with_hidapi(|hidapi| {
+            let info = options.find(hidapi).ok_or(Error::DeviceNotFound)?;
+            let device = info.open_device(hidapi).map_err(Error::from_open)?;
+            Ok((
+                Box::new(device) as Box<dyn Backend>,
+                Some(DeviceInfo::from_hid(info)),
+            ))
+        })
+    }
+
+    #[cfg(feature = "hid")]
+    fn open_all(options: &OpenOptions) -> Result<Vec<Self>> {
+        options.validate()?;
+
+        with_hidapi(|hidapi| {
+            hidapi
+                .device_list()
+                .filter(|info| options.matches(info))
+                .map(|info| {
+                    let device = Box::new(info.open_device(hidapi).map_err(Error::from_open)?);
+                    let mut sensor = Self::with_backend(device, options)?;
+                    sensor.info = RefCell::new(Some(DeviceInfo::from_hid(info)));
+                    let mut options = options.clone();
+                    options.with_path(info.path().to_owned());
+                    sensor.reopen = Some(Box::new(move || Self::open_device(&options)));
+                    Ok(sensor)
+                })
+                .collect()
+        })
+    }
+
+    fn with_backend(device: Box<dyn Backend>, options: &OpenOptions) -> Result<Self> {
+        let key = match options.key_type {
+            KeyType::Fixed(key) => key,
+            KeyType::Random => random_key(),
+        };
+        send_key(device.as_ref(), key)?;
+
+        let timeout = timeout_millis(options.timeout)?;
+
+        let air_control = Self {
+            device: RefCell::new(device),
+            key: Cell::new(key),
+            timeout,
+            clock: Box::new(SystemClock),
+            stats: Cell::new(Stats::default()),
+            zero_humidity: Cell::new(Some(0)),
+            seen: Cell::new(Capabilities::default()),
+            read_strategy: options.read_strategy,
+            latest_temperature: Cell::new(None),
+            latest_co2: Cell::new(None),
+            pending: Cell::new(None),
+            received: Cell::new(None),
+            reinitialize_after: options.reinitialize_after,
+            consecutive_timeouts: Cell::new(0),
+            #[cfg(feature = "hid")]
+            info: RefCell::new(None),
+            #[cfg(feature = "hid")]
+            reopen: None,
+            #[cfg(feature = "hid")]
+            auto_reconnect: options.auto_reconnect.clone(),
+        };
+        Ok(air_control)
+    }
+
+    /// Returns the read timeout.
+    ///
+    /// The timeout is stored in milliseconds, so this might differ from the
+    /// value that was set.
     ///
     /// # Example
     ///
     /// ```no_run
-    /// # use co2mon::OpenOptions;
-    /// # use std::error::Error;
-    /// # use std::ffi::CString;
-    /// # use std::result::Result;
-    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// # use co2mon::{Result, Sensor};
+    /// # use std::time::Duration;
+    /// # #[cfg(feature = "hid")]
+    /// # fn main() -> Result<()> {
     /// #
-    /// let sensor = OpenOptions::new()
-    ///     .with_path(CString::new("/dev/bus/usb/001/004")?)
-    ///     .open()?;
+    /// let sensor = Sensor::open_default()?;
+    /// assert_eq!(sensor.timeout(), Some(Duration::from_secs(5)));
     /// #
     /// # Ok(())
     /// # }
-    pub fn with_path(&mut self, path: CString) -> &mut Self {
-        self.path_type = DevicePathType::Path(path);
-        self
+    /// # #[cfg(not(feature = "hid"))]
+    /// # fn main() {}
+    pub fn timeout(&self) -> Option<Duration> {
+        u64::try_from(self.timeout).ok().map(Duration::from_millis)
+    }
+
+    /// Changes the read timeout.
+    ///
+    /// See [`OpenOptions::timeout`].
+    ///
+    /// # Errors
+    ///
+    /// [`Error::InvalidTimeout`] will be returned if the timeout is too large,
+    /// or shorter than a millisecond, in which case the previous value is
+    /// kept.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use co2mon::{Result, Sensor};
+    /// # use std::time::Duration;
+    /// # #[cfg(feature = "hid")]
+    /// # fn main() -> Result<()> {
+    /// #
+    /// let mut sensor = Sensor::open_default()?;
+    /// sensor.set_timeout(Some(Duration::from_secs(1)))?;
+    /// let reading = sensor.read_one()?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "hid"))]
+    /// # fn main() {}
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) -> Result<()> {
+        self.timeout = timeout_millis(timeout)?;
+        Ok(())
+    }
+
+    /// Returns the encryption key.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use co2mon::{Result, Sensor};
+    /// # #[cfg(feature = "hid")]
+    /// # fn main() -> Result<()> {
+    /// #
+    /// let sensor = Sensor::open_default()?;
+    /// assert_eq!(sensor.key(), [0; 8]);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "hid"))]
+    /// # fn main() {}
+    pub fn key(&self) -> [u8; 8] {
+        self.key.get()
+    }
+
+    /// Changes the encryption key.
+    ///
+    /// The new key is sent to the device, and used to decrypt the following
+    /// readings.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if the key could not be sent, in which case
+    /// the previous key is kept.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use co2mon::{Result, Sensor};
+    /// # #[cfg(feature = "hid")]
+    /// # fn main() -> Result<()> {
+    /// #
+    /// let mut sensor = Sensor::open_default()?;
+    /// sensor.set_key([0x62, 0xea, 0x1d, 0x4f, 0x14, 0xfa, 0xe5, 0x6c])?;
+    /// let reading = sensor.read_one()?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "hid"))]
+    /// # fn main() {}
+    pub fn set_key(&mut self, key: [u8; 8]) -> Result<()> {
+        send_key(self.device.get_mut().as_ref(), key)?;
+        self.key.set(key);
+        Ok(())
+    }
+
+    /// Returns information about the USB device, if the sensor was opened
+    /// using [`OpenOptions::open`] or [`OpenOptions::open_all`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use co2mon::{Result, Sensor};
+    /// # fn main() -> Result<()> {
+    /// #
+    /// let sensor = Sensor::open_default()?;
+    /// if let Some(info) = sensor.device_info() {
+    ///     println!("{:?}", info.path());
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    #[cfg(feature = "hid")]
+    pub fn device_info(&self) -> Option<DeviceInfo> {
+        self.info.borrow().clone()
+    }
+
+    /// Returns the platform-specific path of the USB device.
+    ///
+    /// See [`Sensor::device_info`].
+    #[cfg(feature = "hid")]
+    pub fn path(&self) -> Option<CString> {
+        self.info.borrow().as_ref().map(|info| info.path().clone())
+    }
+
+    /// Returns the serial number of the USB device, which appears to be the
+    /// firmware version.
+    ///
+    /// See [`Sensor::device_info`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use co2mon::{OpenOptions, Result};
+    /// # fn main() -> Result<()> {
+    /// #
+    /// for sensor in OpenOptions::new().open_all()? {
+    ///     let serial_number = sensor.serial_number().unwrap_or_default();
+    ///     println!("{}: {} ppm CO₂", serial_number, sensor.read()?.co2());
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    #[cfg(feature = "hid")]
+    pub fn serial_number(&self) -> Option<String> {
+        self.info
+            .borrow()
+            .as_ref()
+            .and_then(|info| info.serial_number().map(String::from))
+    }
+
+    /// Returns the USB Product ID of the device.
+    ///
+    /// This tells which of the Product IDs passed to
+    /// [`OpenOptions::with_product_ids`] (or the known ones) was matched.
+    ///
+    /// See [`Sensor::device_info`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use co2mon::{Result, Sensor};
+    /// # fn main() -> Result<()> {
+    /// #
+    /// let sensor = Sensor::open_default()?;
+    /// println!("{:04x?}", sensor.product_id());
+    /// #
+    /// # Ok(())
+    /// # }
+    #[cfg(feature = "hid")]
+    pub fn product_id(&self) -> Option<u16> {
+        self.info.borrow().as_ref().map(DeviceInfo::product_id)
+    }
+
+    /// Returns the manufacturer name of the USB device.
+    ///
+    /// See [`Sensor::device_info`].
+    #[cfg(feature = "hid")]
+    pub fn manufacturer_string(&self) -> Option<String> {
+        self.info
+            .borrow()
+            .as_ref()
+            .and_then(|info| info.manufacturer_string().map(String::from))
+    }
+
+    /// Returns the product name of the USB device.
+    ///
+    /// See [`Sensor::device_info`].
+    #[cfg(feature = "hid")]
+    pub fn product_string(&self) -> Option<String> {
+        self.info
+            .borrow()
+            .as_ref()
+            .and_then(|info| info.product_string().map(String::from))
+    }
+
+    /// Reopens the device, e.g. after it was unplugged and plugged back in.
+    ///
+    /// The device is found in the same way as when it was first opened, and
+    /// the current encryption key is sent to it again. The timeout and the
+    /// statistics are kept.
+    ///
+    /// See also [`OpenOptions::auto_reconnect`], which does this
+    /// automatically.
+    ///
+    /// Sensors returned by [`OpenOptions::open_all`] are reopened by path,
+    /// which might change when the device is plugged in again.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::Unsupported`] will be returned if the sensor was not opened
+    /// from a USB device. Otherwise, an error will be returned if the
+    /// device could not be opened, in which case the old one is kept.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use co2mon::{Error, Result, Sensor};
+    /// # #[cfg(feature = "hid")]
+    /// # fn main() -> Result<()> {
+    /// #
+    /// let mut sensor = Sensor::open_default()?;
+    /// loop {
+    ///     match sensor.read() {
+    ///         Ok(reading) => println!("{} ppm CO₂", reading.co2()),
+    ///         Err(Error::Disconnected) => sensor.reconnect()?,
+    ///         Err(e) => eprintln!("{}", e),
+    ///     }
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "hid"))]
+    /// # fn main() {}
+    pub fn reconnect(&mut self) -> Result<()> {
+        #[cfg(feature = "hid")]
+        if self.reopen.is_some() {
+            return self.reopen();
+        }
+        Err(Error::Unsupported)
+    }
+
+    #[cfg(feature = "hid")]
+    fn reopen(&self) -> Result<()> {
+        let reopen = self.reopen.as_ref().ok_or(Error::Unsupported)?;
+        let (device, info) = reopen()?;
+        send_key(device.as_ref(), self.key.get())?;
+        *self.device.borrow_mut() = device;
+        *self.info.borrow_mut() = info;
+
+        let mut stats = self.stats.get();
+        stats.reconnects += 1;
+        self.stats.set(stats);
+        Ok(())
+    }
+
+    /// Reopens the device after a failed read, following the reconnect
+    /// policy. Returns the original error if there is no policy, or the last
+    /// one if all the attempts failed.
+    fn recover(&self, error: Error) -> Result<()> {
+        #[cfg(feature = "hid")]
+        if let (Some(policy), Some(_)) = (&self.auto_reconnect, &self.reopen) {
+            let mut delay = policy.initial_delay;
+            let mut attempts = 0;
+            loop {
+                self.clock.sleep(delay);
+                match self.reopen() {
+                    Ok(()) => return Ok(()),
+                    Err(e) => {
+                        attempts += 1;
+                        if policy.max_attempts.is_some_and(|max| attempts >= max) {
+                            return Err(e);
+                        }
+                        delay = delay
+                            .checked_mul(2)
+                            .unwrap_or(policy.max_delay)
+                            .min(policy.max_delay);
+                    }
+                }
+            }
+        }
+        Err(error)
+    }
+
+    /// Sends the encryption key to the device again.
+    ///
+    /// This is the handshake done when opening the device, and makes some
+    /// devices that stopped sending reports resume. See also
+    /// [`OpenOptions::reinitialize_after`].
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if the key could not be sent.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use co2mon::{Error, Result, Sensor};
+    /// # #[cfg(feature = "hid")]
+    /// # fn main() -> Result<()> {
+    /// #
+    /// let sensor = Sensor::open_default()?;
+    /// if let Err(Error::Timeout) = sensor.read() {
+    ///     sensor.reinitialize()?;
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "hid"))]
+    /// # fn main() {}
+    pub fn reinitialize(&self) -> Result<()> {
+        self.consecutive_timeouts.set(0);
+        send_key(self.device.borrow().as_ref(), self.key.get())
+    }
+
+    /// Returns the read counters.
+    ///
+    /// See [`Stats`].
+    pub fn stats(&self) -> Stats {
+        self.stats.get()
+    }
+
+    /// Takes a single reading from the sensor.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::Timeout`] will be returned if no message was received within
+    /// the configured timeout. Otherwise, an error will be returned on an I/O
+    /// error or if a message could not be read or decoded.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use co2mon::{Result, Sensor};
+    /// # #[cfg(feature = "hid")]
+    /// # fn main() -> Result<()> {
+    /// #
+    /// let sensor = Sensor::open_default()?;
+    /// let reading = sensor.read_one()?;
+    /// println!("{:?}", reading);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "hid"))]
+    /// # fn main() {}
+    pub fn read_one(&self) -> Result<SingleReading> {
+        self.read_report(self.timeout)?.ok_or(Error::Timeout)
+    }
+
+    /// Takes a single reading from the sensor, and returns it along with the
+    /// time at which it was received.
+    ///
+    /// Unlike checking the time after the call, this is not delayed by the
+    /// processing of the report.
+    ///
+    /// # Errors
+    ///
+    /// See [`Sensor::read_one`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use co2mon::{Result, Sensor};
+    /// # #[cfg(feature = "hid")]
+    /// # fn main() -> Result<()> {
+    /// #
+    /// let sensor = Sensor::open_default()?;
+    /// let reading = sensor.read_one_timestamped()?;
+    /// println!("{:?}: {:?}", reading.system_time(), reading.value());
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "hid"))]
+    /// # fn main() {}
+    pub fn read_one_timestamped(&self) -> Result<Timestamped<SingleReading>> {
+        let reading = self.read_one()?;
+        Ok(self.timestamp(reading))
+    }
+
+    /// Takes a reading like [`Sensor::read`], and returns it along with the
+    /// time at which its last report was received.
+    ///
+    /// # Errors
+    ///
+    /// See [`Sensor::read`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use co2mon::{Result, Sensor};
+    /// # #[cfg(feature = "hid")]
+    /// # fn main() -> Result<()> {
+    /// #
+    /// let sensor = Sensor::open_default()?;
+    /// let reading = sensor.read_timestamped()?;
+    /// println!("{:?}: {} ppm CO₂", reading.system_time(), reading.value().co2());
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "hid"))]
+    /// # fn main() {}
+    pub fn read_timestamped(&self) -> Result<Timestamped<Reading>> {
+        let reading = self.read()?;
+        Ok(self.timestamp(reading))
+    }
+
+    pub(crate) fn timestamp<T>(&self, value: T) -> Timestamped<T> {
+        let (instant, system_time) = self
+            .received
+            .get()
+            .expect("a report was received before returning a reading");
+        Timestamped {
+            value,
+            instant,
+            system_time,
+        }
+    }
+
+    /// Takes a single reading from the sensor, waiting until `deadline` at
+    /// most.
+    ///
+    /// The configured timeout is ignored.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::Timeout`] will be returned if no message was received before
+    /// the deadline. Otherwise, an error will be returned on an I/O error or
+    /// if a message could not be read or decoded.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use co2mon::{Result, Sensor};
+    /// # use std::time::{Duration, Instant};
+    /// # #[cfg(feature = "hid")]
+    /// # fn main() -> Result<()> {
+    /// #
+    /// let sensor = Sensor::open_default()?;
+    /// let deadline = Instant::now() + Duration::from_secs(1);
+    /// let reading = sensor.read_one_until(deadline)?;
+    /// println!("{:?}", reading);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "hid"))]
+    /// # fn main() {}
+    pub fn read_one_until(&self, deadline: Instant) -> Result<SingleReading> {
+        let remaining = deadline.saturating_duration_since(self.clock.now());
+        if remaining.is_zero() {
+            return Err(Error::Timeout);
+        }
+        // round up, so we don't return before the deadline
+        let timeout = remaining.as_nanos().div_ceil(1_000_000);
+        let timeout = i32::try_from(timeout).unwrap_or(i32::MAX);
+        self.read_report(timeout)?.ok_or(Error::Timeout)
+    }
+
+    /// Takes a single reading from the sensor, waiting for `timeout` at
+    /// most.
+    ///
+    /// This is the same as [`Sensor::read_one_until`] with a deadline of
+    /// `timeout` from now.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::Timeout`] will be returned if no message was received in
+    /// time. Otherwise, an error will be returned on an I/O error or if a
+    /// message could not be read or decoded.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use co2mon::{Result, Sensor};
+    /// # use std::time::Duration;
+    /// # #[cfg(feature = "hid")]
+    /// # fn main() -> Result<()> {
+    /// #
+    /// let sensor = Sensor::open_default()?;
+    /// let reading = sensor.read_one_timeout(Duration::from_secs(30))?;
+    /// println!("{:?}", reading);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "hid"))]
+    /// # fn main() {}
+    pub fn read_one_timeout(&self, timeout: Duration) -> Result<SingleReading> {
+        self.read_one_by(self.deadline_after(timeout))
+    }
+
+    /// Returns the instant `timeout` from now, or `None` if it's too far in
+    /// the future to be represented, in which case there is no deadline.
+    fn deadline_after(&self, timeout: Duration) -> Option<Instant> {
+        self.clock.now().checked_add(timeout)
+    }
+
+    /// Like [`Sensor::read_one_until`], but waits forever without a deadline.
+    fn read_one_by(&self, deadline: Option<Instant>) -> Result<SingleReading> {
+        match deadline {
+            Some(deadline) => self.read_one_until(deadline),
+            None => self.read_report(-1)?.ok_or(Error::Timeout),
+        }
+    }
+
+    /// Reads a raw report from the device, without decoding it, returning
+    /// `None` if no report arrived within `timeout`.
+    ///
+    /// This is meant for diagnostics, see [`decode_report`] to decode the
+    /// report. The raw reports are not counted in [`Sensor::stats`].
+    ///
+    /// # Errors
+    ///
+    /// [`Error::InvalidMessage`] will be returned on a short read. Otherwise,
+    /// an error will be returned on an I/O error.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use co2mon::{Result, Sensor};
+    /// # use std::time::Duration;
+    /// # #[cfg(feature = "hid")]
+    /// # fn main() -> Result<()> {
+    /// #
+    /// let sensor = Sensor::open_default()?;
+    /// if let Some(report) = sensor.read_raw(Duration::from_secs(5))? {
+    ///     println!("{:02x?}", report);
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "hid"))]
+    /// # fn main() {}
+    pub fn read_raw(&self, timeout: Duration) -> Result<Option<[u8; 8]>> {
+        // round up, so we don't return before the timeout
+        let timeout = timeout.as_nanos().div_ceil(1_000_000);
+        let timeout = i32::try_from(timeout).unwrap_or(i32::MAX);
+        match self.read_frame(timeout)? {
+            (_, 0) => Ok(None),
+            (data, 8) => Ok(Some(data)),
+            _ => Err(Error::InvalidMessage),
+        }
+    }
+
+    /// Reads a report, reconnecting if needed, and returns it along with its
+    /// length.
+    fn read_frame(&self, timeout: i32) -> Result<([u8; 8], usize)> {
+        let mut data = [0; 8];
+        loop {
+            // the device is replaced when reconnecting, so don't keep it borrowed
+            let read = self.device.borrow().read_timeout(&mut data, timeout);
+            match read {
+                Ok(len) => return Ok((data, len)),
+                Err(e @ Error::Io(_)) => self.recover(e)?,
+                Err(e @ Error::Disconnected) => self.recover(e)?,
+                #[cfg(feature = "hid")]
+                Err(e @ Error::Hid(_)) => self.recover(e)?,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Reads and decodes a report, returning `None` if the timeout expired.
+    fn read_report(&self, timeout: i32) -> Result<Option<SingleReading>> {
+        let start = self.clock.now();
+        let (data, len) = self.read_frame(timeout)?;
+        let received = (len == data.len()).then(|| self.clock.now());
+        let result = match len {
+            0 => Ok(None),
+            8 => decode_report(data, self.key.get()).map(Some),
+            _ => Err(Error::InvalidMessage),
+        };
+
+        let mut stats = self.stats.get();
+        if let Some(received) = received {
+            stats.frames += 1;
+            stats.record_latency(received.saturating_duration_since(start));
+        }
+        match result {
+            Ok(None) => stats.timeouts += 1,
+            Ok(Some(ref reading)) => {
+                self.received
+                    .set(received.map(|received| (received, SystemTime::now())));
+                let mut seen = self.seen.get();
+                match *reading {
+                    SingleReading::CO2(val) => {
+                        seen.co2 = true;
+                        self.latest_co2.set(Some(val));
+                    }
+                    SingleReading::Temperature(val) => {
+                        seen.temperature = true;
+                        self.latest_temperature.set(Some(val));
+                    }
+                    SingleReading::Humidity(val) => {
+                        let zero_humidity = self.zero_humidity.get();
+                        self.zero_humidity
+                            .set(zero_humidity.filter(|_| val == 0.0).map(|count| count + 1));
+                        seen.humidity = self.humidity_supported() == Some(true);
+                    }
+                    _ => {}
+                }
+                self.seen.set(seen);
+            }
+            Err(Error::Checksum) => stats.checksum_errors += 1,
+            Err(_) => stats.invalid_messages += 1,
+        }
+        self.stats.set(stats);
+
+        if let Ok(None) = result {
+            let timeouts = self.consecutive_timeouts.get() + 1;
+            self.consecutive_timeouts.set(timeouts);
+            if self.reinitialize_after == Some(timeouts) {
+                self.reinitialize()?;
+            }
+        } else {
+            self.consecutive_timeouts.set(0);
+        }
+        result
+    }
+
+    /// Takes a multiple readings from the sensor until the temperature and
+    /// CO₂ concentration are available, and returns both.
+    ///
+    /// See [`OpenOptions::read_strategy`] for a way to reuse the values that
+    /// were received before.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::Timeout`] will be returned if the sensor did not report both
+    /// values within the configured timeout. Otherwise, an error will be
+    /// returned on an I/O error or if a message could not be read or decoded.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use co2mon::{Result, Sensor};
+    /// # #[cfg(feature = "hid")]
+    /// # fn main() -> Result<()> {
+    /// #
+    /// let sensor = Sensor::open_default()?;
+    /// let reading = sensor.read()?;
+    /// println!("{} °C, {} ppm CO₂", reading.temperature(), reading.co2());
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "hid"))]
+    /// # fn main() {}
+    pub fn read(&self) -> Result<Reading> {
+        if self.timeout == -1 {
+            return self.read_with(|| self.read_one());
+        }
+
+        let deadline = self.deadline_after(Duration::from_millis(self.timeout as u64));
+        self.read_with(|| self.read_one_by(deadline))
+    }
+
+    /// Takes a multiple readings from the sensor until the temperature and
+    /// CO₂ concentration are available or `deadline` is reached.
+    ///
+    /// The configured timeout is ignored.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::Timeout`] will be returned if the sensor did not report both
+    /// values before the deadline. Otherwise, an error will be returned on an
+    /// I/O error or if a message could not be read or decoded.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use co2mon::{Result, Sensor};
+    /// # use std::time::{Duration, Instant};
+    /// # #[cfg(feature = "hid")]
+    /// # fn main() -> Result<()> {
+    /// #
+    /// let sensor = Sensor::open_default()?;
+    /// let deadline = Instant::now() + Duration::from_secs(10);
+    /// let reading = sensor.read_until(deadline)?;
+    /// println!("{} °C, {} ppm CO₂", reading.temperature(), reading.co2());
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "hid"))]
+    /// # fn main() {}
+    pub fn read_until(&self, deadline: Instant) -> Result<Reading> {
+        self.read_with(|| self.read_one_until(deadline))
+    }
+
+    /// Reads several sensors at once, each until the temperature and CO₂
+    /// concentration are available or `deadline` is reached.
+    ///
+    /// Each sensor is read in its own thread, so this takes about as long as
+    /// the slowest sensor instead of the sum of all of them. The sensors are
+    /// borrowed mutably because a [`Sensor`] can't be shared between threads.
+    ///
+    /// The results are in the same order as `sensors`, and the configured
+    /// timeouts are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Each result holds the error of its own sensor, like those of
+    /// [`Sensor::read_until`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use co2mon::{OpenOptions, Result, Sensor};
+    /// # use std::time::{Duration, Instant};
+    /// # #[cfg(feature = "hid")]
+    /// # fn main() -> Result<()> {
+    /// #
+    /// let mut sensors = vec![
+    ///     OpenOptions::new().with_serial_number("1.40").open()?,
+    ///     OpenOptions::new().with_serial_number("1.41").open()?,
+    /// ];
+    /// let deadline = Instant::now() + Duration::from_secs(10);
+    /// for reading in Sensor::read_many(&mut sensors, deadline) {
+    ///     match reading {
+    ///         Ok(reading) => println!("{} ppm CO₂", reading.co2()),
+    ///         Err(e) => println!("{}", e),
+    ///     }
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "hid"))]
+    /// # fn main() {}
+    pub fn read_many(sensors: &mut [Sensor], deadline: Instant) -> Vec<Result<Reading>> {
+        thread::scope(|scope| {
+            let threads = sensors
+                .iter_mut()
+                .map(|sensor| scope.spawn(move || sensor.read_until(deadline)))
+                .collect::<Vec<_>>();
+            threads
+                .into_iter()
+                .map(|thread| thread.join().unwrap_or_else(|e| panic::resume_unwind(e)))
+                .collect()
+        })
+    }
+
+    /// Takes readings from the sensor until the CO₂ concentration (in ppm) is available, and
+    /// returns it.
+    ///
+    /// The other values reported in the meantime are discarded.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::Timeout`] will be returned if the value was not reported
+    /// before the timeout expired. Otherwise, an error will be returned on an
+    /// I/O error or if a message could not be read or decoded.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use co2mon::{Result, Sensor};
+    /// # #[cfg(feature = "hid")]
+    /// # fn main() -> Result<()> {
+    /// #
+    /// let sensor = Sensor::open_default()?;
+    /// println!("{} ppm CO₂", sensor.read_co2()?);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "hid"))]
+    /// # fn main() {}
+    pub fn read_co2(&self) -> Result<u16> {
+        self.read_value(|reading| match reading {
+            SingleReading::CO2(val) => Some(val),
+            _ => None,
+        })
+    }
+
+    /// Takes readings from the sensor until the temperature (in °C) is available, and
+    /// returns it.
+    ///
+    /// The other values reported in the meantime are discarded.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::Timeout`] will be returned if the value was not reported
+    /// before the timeout expired. Otherwise, an error will be returned on an
+    /// I/O error or if a message could not be read or decoded.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use co2mon::{Result, Sensor};
+    /// # #[cfg(feature = "hid")]
+    /// # fn main() -> Result<()> {
+    /// #
+    /// let sensor = Sensor::open_default()?;
+    /// println!("{} °C", sensor.read_temperature()?);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "hid"))]
+    /// # fn main() {}
+    pub fn read_temperature(&self) -> Result<f32> {
+        self.read_value(|reading| match reading {
+            SingleReading::Temperature(val) => Some(val),
+            _ => None,
+        })
+    }
+
+    /// Takes readings from the sensor until the relative humidity (in %) is
+    /// available, and returns it.
+    ///
+    /// The other values reported in the meantime are discarded. Some devices
+    /// report a humidity of `0`, but don't actually measure it. Once this is
+    /// detected (see [`Sensor::humidity_supported`]), `None` is returned.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::Timeout`] will be returned if the value was not reported
+    /// before the timeout expired. Otherwise, an error will be returned on an
+    /// I/O error or if a message could not be read or decoded.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use co2mon::{Result, Sensor};
+    /// # #[cfg(feature = "hid")]
+    /// # fn main() -> Result<()> {
+    /// #
+    /// let sensor = Sensor::open_default()?;
+    /// if let Some(humidity) = sensor.read_humidity()? {
+    ///     println!("{} % RH", humidity);
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "hid"))]
+    /// # fn main() {}
+    pub fn read_humidity(&self) -> Result<Option<f32>> {
+        if self.humidity_supported() == Some(false) {
+            return Ok(None);
+        }
+        self.read_value(|reading| match (reading, self.humidity_supported()) {
+            (SingleReading::Humidity(val), Some(true)) => Some(Some(val)),
+            (_, Some(false)) => Some(None),
+            _ => None,
+        })
+    }
+
+    /// Returns whether the device measures the relative humidity, or `None`
+    /// if that's not known yet.
+    ///
+    /// Devices without a humidity sensor, like the AIRCO2NTROL MINI, still
+    /// send humidity readings, but they are always `0`. This is determined
+    /// from the readings seen so far: the humidity is assumed unsupported
+    /// after a few readings of `0`, and supported after any other value.
+    ///
+    /// [`Sensor::read_one`] returns the humidity readings as they are.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use co2mon::{Result, Sensor};
+    /// # #[cfg(feature = "hid")]
+    /// # fn main() -> Result<()> {
+    /// #
+    /// let sensor = Sensor::open_default()?;
+    /// let _ = sensor.read_humidity();
+    /// if sensor.humidity_supported() == Some(false) {
+    ///     println!("no humidity sensor");
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "hid"))]
+    /// # fn main() {}
+    pub fn humidity_supported(&self) -> Option<bool> {
+        match self.zero_humidity.get() {
+            None => Some(true),
+            Some(count) if count >= ZERO_HUMIDITY_READINGS => Some(false),
+            Some(_) => None,
+        }
+    }
+
+    /// Takes a multiple readings from the sensor until the temperature and
+    /// CO₂ concentration are available or `timeout` has passed.
+    ///
+    /// This is the same as [`Sensor::read_until`] with a deadline of
+    /// `timeout` from now.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::Timeout`] will be returned if the sensor did not report both
+    /// values in time. Otherwise, an error will be returned on an I/O error
+    /// or if a message could not be read or decoded.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use co2mon::{Result, Sensor};
+    /// # use std::time::Duration;
+    /// # #[cfg(feature = "hid")]
+    /// # fn main() -> Result<()> {
+    /// #
+    /// let sensor = Sensor::open_default()?;
+    /// // the first reading can take longer
+    /// let reading = sensor.read_timeout(Duration::from_secs(30))?;
+    /// println!("{} °C, {} ppm CO₂", reading.temperature(), reading.co2());
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "hid"))]
+    /// # fn main() {}
+    pub fn read_timeout(&self, timeout: Duration) -> Result<Reading> {
+        let deadline = self.deadline_after(timeout);
+        self.read_with(|| self.read_one_by(deadline))
+    }
+
+    /// Takes `n` single readings from the sensor, waiting for `timeout` at
+    /// most overall.
+    ///
+    /// # Errors
+    ///
+    /// If a read fails, the readings taken so far are returned along with
+    /// the error. [`Error::Timeout`] will be returned if `timeout` passes
+    /// before all the readings are received.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use co2mon::{Result, Sensor};
+    /// # use std::time::Duration;
+    /// # #[cfg(feature = "hid")]
+    /// # fn main() -> Result<()> {
+    /// #
+    /// let sensor = Sensor::open_default()?;
+    /// // skip the first reports after opening the device
+    /// let _ = sensor.read_one_n(10, Duration::from_secs(30));
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "hid"))]
+    /// # fn main() {}
+    pub fn read_one_n(
+        &self,
+        n: usize,
+        timeout: Duration,
+    ) -> result::Result<Vec<SingleReading>, PartialRead<SingleReading>> {
+        let deadline = self.deadline_after(timeout);
+        read_n(n, || self.read_one_by(deadline))
+    }
+
+    /// Takes `n` readings from the sensor like [`Sensor::read`], waiting for
+    /// `timeout` at most overall.
+    ///
+    /// # Errors
+    ///
+    /// If a read fails, the readings taken so far are returned along with
+    /// the error. [`Error::Timeout`] will be returned if `timeout` passes
+    /// before all the readings are received.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use co2mon::{Result, Sensor};
+    /// # use std::time::Duration;
+    /// # #[cfg(feature = "hid")]
+    /// # fn main() -> Result<()> {
+    /// #
+    /// let sensor = Sensor::open_default()?;
+    /// let readings = match sensor.read_n(5, Duration::from_secs(60)) {
+    ///     Ok(readings) => readings,
+    ///     Err(e) => e.into_parts().0,
+    /// };
+    /// if !readings.is_empty() {
+    ///     let total = readings.iter().map(|r| u32::from(r.co2())).sum::<u32>();
+    ///     println!("{} ppm CO₂", total / readings.len() as u32);
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "hid"))]
+    /// # fn main() {}
+    pub fn read_n(
+        &self,
+        n: usize,
+        timeout: Duration,
+    ) -> result::Result<Vec<Reading>, PartialRead<Reading>> {
+        let deadline = self.deadline_after(timeout);
+        read_n(n, || self.read_with(|| self.read_one_by(deadline)))
+    }
+
+    /// Returns an endless iterator calling [`Sensor::read`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use co2mon::{Result, Sensor};
+    /// # #[cfg(feature = "hid")]
+    /// # fn main() -> Result<()> {
+    /// #
+    /// let sensor = Sensor::open_default()?;
+    /// for reading in sensor.readings().filter_map(Result::ok).step_by(10).take(6) {
+    ///     println!("{} ppm CO₂", reading.co2());
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "hid"))]
+    /// # fn main() {}
+    pub fn readings(&self) -> Readings<'_> {
+        Readings { sensor: self }
+    }
+
+    /// Returns an endless iterator calling [`Sensor::read_one`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use co2mon::{Result, Sensor, SingleReading};
+    /// # #[cfg(feature = "hid")]
+    /// # fn main() -> Result<()> {
+    /// #
+    /// let sensor = Sensor::open_default()?;
+    /// let unknown = sensor
+    ///     .single_readings()
+    ///     .take(100)
+    ///     .filter(|reading| matches!(reading, Ok(SingleReading::Unknown(..))))
+    ///     .count();
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "hid"))]
+    /// # fn main() {}
+    pub fn single_readings(&self) -> SingleReadings<'_> {
+        SingleReadings { sensor: self }
+    }
+
+    /// Takes readings from the sensor for a full reporting cycle, and
+    /// returns all the values.
+    ///
+    /// The cycle is assumed to be over when a kind of reading repeats, after
+    /// the temperature and CO₂ concentration are available. That reading is
+    /// kept as the start of the next cycle, for the next call.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::Timeout`] will be returned if the cycle did not end before the
+    /// timeout expired. Otherwise, an error will be returned on an I/O error
+    /// or if a message could not be read or decoded.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use co2mon::{Result, Sensor};
+    /// # #[cfg(feature = "hid")]
+    /// # fn main() -> Result<()> {
+    /// #
+    /// let sensor = Sensor::open_default()?;
+    /// let reading = sensor.read_all()?;
+    /// println!("{} °C, {} ppm CO₂", reading.temperature(), reading.co2());
+    /// if let Some(humidity) = reading.humidity() {
+    ///     println!("{} % RH", humidity);
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "hid"))]
+    /// # fn main() {}
+    pub fn read_all(&self) -> Result<FullReading> {
+        let mut opcodes = Vec::new();
+        let mut temperature = None;
+        let mut co2 = None;
+        let mut humidity = None;
+        let mut other = Vec::<(u8, u16)>::new();
+        let mut add = |reading: SingleReading| {
+            // the unknown readings are told apart by their opcode
+            let kind = match reading {
+                SingleReading::Unknown(opcode, _) => (mem::discriminant(&reading), Some(opcode)),
+                _ => (mem::discriminant(&reading), None),
+            };
+            if opcodes.contains(&kind) {
+                if let (Some(temperature), Some(co2)) = (temperature, co2) {
+                    self.pending.set(Some(reading));
+                    return Some(FullReading {
+                        reading: Reading { temperature, co2 },
+                        humidity: humidity.filter(|_| self.humidity_supported() == Some(true)),
+                        other: mem::take(&mut other),
+                    });
+                }
+                opcodes.clear();
+            }
+            opcodes.push(kind);
+
+            match reading {
+                SingleReading::Temperature(val) => temperature = Some(val),
+                SingleReading::CO2(val) => co2 = Some(val),
+                SingleReading::Humidity(val) => humidity = Some(val),
+                SingleReading::Unknown(opcode, val) => {
+                    match other.iter_mut().find(|(op, _)| *op == opcode) {
+                        Some(entry) => entry.1 = val,
+                        None => other.push((opcode, val)),
+                    }
+                }
+                _ => {}
+            }
+            None
+        };
+        if let Some(reading) = self.pending.take() {
+            add(reading);
+        }
+        self.read_value(add)
+    }
+
+    /// Returns the kinds of readings the device produces.
+    ///
+    /// This takes readings until every kind was seen or `duration` has
+    /// passed, whichever comes first. The readings taken before, including
+    /// the ones returned by the other methods, are also taken into account.
+    ///
+    /// A few seconds should be enough, since the devices send their readings
+    /// in a quick succession.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned on an I/O error. Invalid messages are
+    /// ignored.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use co2mon::{Result, Sensor};
+    /// # use std::time::Duration;
+    /// # #[cfg(feature = "hid")]
+    /// # fn main() -> Result<()> {
+    /// #
+    /// let sensor = Sensor::open_default()?;
+    /// let capabilities = sensor.capabilities(Duration::from_secs(10))?;
+    /// if capabilities.humidity() {
+    ///     println!("{:?} % RH", sensor.read_humidity()?);
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "hid"))]
+    /// # fn main() {}
+    pub fn capabilities(&self, duration: Duration) -> Result<Capabilities> {
+        let deadline = self.deadline_after(duration);
+        loop {
+            let seen = self.seen.get();
+            if seen.co2 && seen.temperature && seen.humidity {
+                return Ok(seen);
+            }
+            match self.read_one_by(deadline) {
+                Ok(_) | Err(Error::Checksum) | Err(Error::InvalidMessage) => {}
+                Err(Error::Timeout) => return Ok(seen),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    fn read_value<T, F>(&self, mut select: F) -> Result<T>
+    where
+        F: FnMut(SingleReading) -> Option<T>,
+    {
+        let deadline = if self.timeout == -1 {
+            None
+        } else {
+            self.deadline_after(Duration::from_millis(self.timeout as u64))
+        };
+        loop {
+            let reading = match deadline {
+                Some(deadline) => self.read_one_until(deadline)?,
+                None => self.read_one()?,
+            };
+            if let Some(val) = select(reading) {
+                return Ok(val);
+            }
+        }
+    }
+
+    fn read_with<F>(&self, mut read_one: F) -> Result<Reading>
+    where
+        F: FnMut() -> Result<SingleReading>,
+    {
+        let (mut temperature, mut co2) = match self.read_strategy {
+            ReadStrategy::Fresh => (None, None),
+            ReadStrategy::FreshCo2 => (self.latest_temperature.get(), None),
+            ReadStrategy::Latest => (self.latest_temperature.get(), self.latest_co2.get()),
+        };
+        loop {
+            match read_one()? {
+                SingleReading::Temperature(val) => temperature = Some(val),
+                SingleReading::CO2(val) => co2 = Some(val),
+                _ => {}
+            }
+            if let (Some(temperature), Some(co2)) = (temperature, co2) {
+                let reading = Reading { temperature, co2 };
+                return Ok(reading);
+            }
+        }
+    }
+}
+
+fn random_key() -> [u8; 8] {
+    let mut key = [0; 8];
+    if os_random(&mut key) {
+        return key;
+    }
+
+    // each `RandomState` uses different keys, seeded by the OS
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos()),
+    );
+    hasher.finish().to_ne_bytes()
+}
+
+/// Fills `buf` with random bytes from the OS, returning whether it worked.
+#[cfg(unix)]
+fn os_random(buf: &mut [u8]) -> bool {
+    File::open("/dev/urandom")
+        .and_then(|mut file| file.read_exact(buf))
+        .is_ok()
+}
+
+#[cfg(not(unix))]
+fn os_random(_buf: &mut [u8]) -> bool {
+    false
+}
+
+/// The `hidapi` context, which is shared because only one can exist at a
+/// time. It is created on first use and kept until the process exits.
+#[cfg(feature = "hid")]
+static HIDAPI: Mutex<Option<HidApi>> = Mutex::new(None);
+
+/// Runs `f` with the shared `hidapi` context, after updating its list of
+/// devices.
+#[cfg(feature = "hid")]
+fn with_hidapi<T>(f: impl FnOnce(&HidApi) -> Result<T>) -> Result<T> {
+    let mut hidapi = HIDAPI.lock().unwrap_or_else(PoisonError::into_inner);
+    match *hidapi {
+        Some(ref mut hidapi) => hidapi.refresh_devices()?,
+        None => *hidapi = Some(HidApi::new()?),
+    }
+    f(hidapi.as_ref().expect("the context was created above"))
+}
+
+fn read_n<T, F>(n: usize, mut read: F) -> result::Result<Vec<T>, PartialRead<T>>
+where
+    F: FnMut() -> Result<T>,
+{
+    let mut readings = Vec::with_capacity(n);
+    while readings.len() < n {
+        match read() {
+            Ok(reading) => readings.push(reading),
+            Err(error) => return Err(PartialRead { readings, error }),
+        }
+    }
+    Ok(readings)
+}
+
+fn send_key(device: &dyn Backend, key: [u8; 8]) -> Result<()> {
+    // fill in the Report Id
+    let frame = {
+        let mut frame = [0; 9];
+        frame[1..9].copy_from_slice(&key);
+        frame
+    };
+    device.send_feature_report(&frame)
+}
+
+/// Parses an encryption key written as 16 hexadecimal digits.
+fn parse_key(s: &str) -> Option<[u8; 8]> {
+    if s.len() != 16 || !s.is_ascii() {
+        return None;
+    }
+    let mut key = [0; 8];
+    for (i, byte) in key.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).ok()?;
+    }
+    Some(key)
+}
+
+fn timeout_millis(timeout: Option<Duration>) -> Result<i32> {
+    match timeout.map(|timeout| timeout.as_millis()) {
+        // `0` would make the reads non-blocking
+        Some(0) => Err(Error::InvalidTimeout),
+        timeout => timeout
+            .map_or(Ok(-1), i32::try_from)
+            .map_err(|_| Error::InvalidTimeout),
+    }
+}
+
+/// Decodes a raw report read from the USB device.
+///
+/// Both the encrypted reports and the plain ones sent by the newer devices
+/// are supported. `key` is the encryption key the device was opened with.
+///
+/// # Example
+///
+/// ```
+/// # use co2mon::{Result, SingleReading};
+/// # fn main() -> Result<()> {
+/// #
+/// let report = [0x6c, 0xa4, 0xa2, 0xb6, 0x5d, 0x9a, 0x9c, 0x08];
+/// let reading = co2mon::decode_report(report, [0; 8])?;
+/// assert_eq!(reading, SingleReading::CO2(1111));
+/// #
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// An error will be returned if the report could not be decoded.
+pub fn decode_report(report: [u8; 8], key: [u8; 8]) -> Result<SingleReading> {
+    // if the "magic byte" is present no decryption is necessary. This is the case for AIRCO2NTROL COACH
+    // and newer AIRCO2NTROL MINIs in general
+    let data = if report[4] == 0x0d {
+        report
+    } else {
+        decrypt(report, key)
+    };
+    let reading = zg_co2::decode([data[0], data[1], data[2], data[3], data[4]])?;
+    Ok(reading)
+}
+
+#[allow(clippy::manual_rotate)]
+fn decrypt(mut data: [u8; 8], key: [u8; 8]) -> [u8; 8] {
+    data.swap(0, 2);
+    data.swap(1, 4);
+    data.swap(3, 7);
+    data.swap(5, 6);
+
+    for (r, k) in data.iter_mut().zip(key.iter()) {
+        *r ^= k;
+    }
+
+    let tmp = data[7] << 5;
+    data[7] = data[6] << 5 | data[7] >> 3;
+    data[6] = data[5] << 5 | data[6] >> 3;
+    data[5] = data[4] << 5 | data[5] >> 3;
+    data[4] = data[3] << 5 | data[4] >> 3;
+    data[3] = data[2] << 5 | data[3] >> 3;
+    data[2] = data[1] << 5 | data[2] >> 3;
+    data[1] = data[0] << 5 | data[1] >> 3;
+    data[0] = tmp | data[0] >> 3;
+
+    for (r, m) in data.iter_mut().zip(b"Htemp99e".iter()) {
+        *r = r.wrapping_sub(m << 4 | m >> 4);
+    }
+
+    data
+}
+
+#[cfg(feature = "hid")]
+#[derive(Debug, Clone)]
+enum DevicePathType {
+    Id,
+    SerialNumber(String),
+    Path(CString),
+    UsbLocation(u8, Vec<u8>),
+}
+
+#[derive(Debug, Clone)]
+enum KeyType {
+    Fixed([u8; 8]),
+    Random,
+}
+
+/// How [`Sensor::read`] combines the readings.
+///
+/// # Example
+///
+/// ```no_run
+/// # use co2mon::{OpenOptions, ReadStrategy, Result};
+/// # #[cfg(feature = "hid")]
+/// # fn main() -> Result<()> {
+/// #
+/// let sensor = OpenOptions::new()
+///     .read_strategy(ReadStrategy::FreshCo2)
+///     .open()?;
+/// let reading = sensor.read()?;
+/// #
+/// # Ok(())
+/// # }
+/// # #[cfg(not(feature = "hid"))]
+/// # fn main() {}
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ReadStrategy {
+    /// Waits until both a new temperature and a new CO₂ concentration were
+    /// received.
+    #[default]
+    Fresh,
+    /// Waits for a new CO₂ concentration, and reuses the last temperature if
+    /// one was received before.
+    FreshCo2,
+    /// Waits for a single new reading of any kind, and combines it with the
+    /// last values received before.
+    ///
+    /// This waits for both values the first time.
+    Latest,
+}
+
+/// Sensor open options.
+///
+/// Opens the first available device with the USB Vendor ID `0x04d9`
+/// and one of the known Product IDs (currently only `0xa052`), a `0`
+/// encryption key and a 5 seconds timeout.
+///
+/// Normally there's no need to change the encryption key.
+///
+/// # Example
+///
+/// ```no_run
+/// # use co2mon::{OpenOptions, Result};
+/// # use std::time::Duration;
+/// # #[cfg(feature = "hid")]
+/// # fn main() -> Result<()> {
+/// #
+/// let sensor = OpenOptions::new()
+///     .timeout(Some(Duration::from_secs(10)))
+///     .open()?;
+/// #
+/// # Ok(())
+/// # }
+/// # #[cfg(not(feature = "hid"))]
+/// # fn main() {}
+#[derive(Debug, Clone)]
+pub struct OpenOptions {
+    #[cfg(feature = "hid")]
+    path_type: DevicePathType,
+    #[cfg(feature = "hid")]
+    vendor_id: u16,
+    #[cfg(feature = "hid")]
+    product_ids: Vec<u16>,
+    key_type: KeyType,
+    timeout: Option<Duration>,
+    read_strategy: ReadStrategy,
+    reinitialize_after: Option<u32>,
+    #[cfg(feature = "hid")]
+    auto_reconnect: Option<ReconnectPolicy>,
+}
+
+impl Default for OpenOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OpenOptions {
+    #[cfg(feature = "hid")]
+    fn validate(&self) -> Result<()> {
+        match self.path_type {
+            DevicePathType::SerialNumber(ref sn) if sn.is_empty() => {
+                return Err(Error::InvalidSerialNumber)
+            }
+            DevicePathType::Path(ref path) if path.as_bytes().is_empty() => {
+                return Err(Error::InvalidPath)
+            }
+            DevicePathType::UsbLocation(_, ref ports) if ports.is_empty() => {
+                return Err(Error::InvalidUsbLocation)
+            }
+            _ => {}
+        }
+        if self.product_ids.is_empty() {
+            return Err(Error::InvalidProductIds);
+        }
+        if let Some(ref policy) = self.auto_reconnect {
+            if policy.initial_delay.is_zero() {
+                return Err(Error::InvalidTimeout);
+            }
+        }
+        timeout_millis(self.timeout)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "hid")]
+    fn matches(&self, info: &hidapi::DeviceInfo) -> bool {
+        let matches_id =
+            || info.vendor_id() == self.vendor_id && self.product_ids.contains(&info.product_id());
+        match self.path_type {
+            DevicePathType::Id => matches_id(),
+            DevicePathType::SerialNumber(ref sn) => {
+                matches_id() && info.serial_number() == Some(sn)
+            }
+            DevicePathType::Path(ref path) => info.path() == path.as_c_str(),
+            DevicePathType::UsbLocation(bus, ref ports) => {
+                matches_id()
+                    && device_info::usb_location(info.path())
+                        .is_some_and(|location| location.0 == bus && location.1 == *ports)
+            }
+        }
+    }
+
+    /// Finds the first matching device, preferring the product IDs in order.
+    #[cfg(feature = "hid")]
+    fn find<'a>(&self, hidapi: &'a HidApi) -> Option<&'a hidapi::DeviceInfo> {
+        hidapi
+            .device_list()
+            .filter(|info| self.matches(info))
+            .min_by_key(|info| {
+                self.product_ids
+                    .iter()
+                    .position(|&pid| pid == info.product_id())
+            })
+    }
+
+    /// Creates a new set of options to be configured.
+    ///
+    /// The defaults are opening the first connected sensor and a timeout of
+    /// 5 seconds.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use co2mon::{OpenOptions, Result};
+    /// # use std::time::Duration;
+    /// # #[cfg(feature = "hid")]
+    /// # fn main() -> Result<()> {
+    /// #
+    /// let sensor = OpenOptions::new()
+    ///     .timeout(Some(Duration::from_secs(10)))
+    ///     .open()?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "hid"))]
+    /// # fn main() {}
+    pub fn new() -> Self {
+        Self {
+            #[cfg(feature = "hid")]
+            path_type: DevicePathType::Id,
+            #[cfg(feature = "hid")]
+            vendor_id: VID,
+            #[cfg(feature = "hid")]
+            product_ids: PRODUCT_IDS.to_vec(),
+            key_type: KeyType::Fixed([0; 8]),
+            timeout: Some(Duration::from_secs(5)),
+            read_strategy: ReadStrategy::Fresh,
+            reinitialize_after: None,
+            #[cfg(feature = "hid")]
+            auto_reconnect: None,
+        }
+    }
+
+    /// Creates a set of options configured from environment variables.
+    ///
+    /// The variables which are not set keep the defaults of
+    /// [`OpenOptions::new`]:
+    ///
+    ///  - `CO2MON_SERIAL`: the serial number, see
+    ///    [`OpenOptions::with_serial_number`]
+    ///  - `CO2MON_PATH`: the device path, see [`OpenOptions::with_path`]
+    ///  - `CO2MON_VID_PID`: the USB Vendor and Product IDs in hexadecimal,
+    ///    like `04d9:a052`, see [`OpenOptions::with_vid_pid`]
+    ///  - `CO2MON_KEY`: the encryption key as 16 hexadecimal digits, or
+    ///    `random`, see [`OpenOptions::with_key`]
+    ///  - `CO2MON_TIMEOUT_MS`: the timeout in milliseconds, or `none`, see
+    ///    [`OpenOptions::timeout`]
+    ///
+    /// The device selection variables are ignored without the `hid` feature.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::InvalidEnvVar`] will be returned if a variable could not be
+    /// parsed. The values themselves are checked when opening the sensor.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use co2mon::{OpenOptions, Result};
+    /// # #[cfg(feature = "hid")]
+    /// # fn main() -> Result<()> {
+    /// #
+    /// let sensor = OpenOptions::from_env()?.open()?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "hid"))]
+    /// # fn main() {}
+    pub fn from_env() -> Result<Self> {
+        Self::from_vars(env::var_os)
+    }
+
+    fn from_vars<F>(var: F) -> Result<Self>
+    where
+        F: Fn(&'static str) -> Option<OsString>,
+    {
+        let var = |name| {
+            var(name)
+                .map(|value| value.into_string().map_err(|_| Error::InvalidEnvVar(name)))
+                .transpose()
+        };
+        let mut options = Self::new();
+
+        #[cfg(feature = "hid")]
+        {
+            if let Some(sn) = var("CO2MON_SERIAL")? {
+                options.with_serial_number(sn);
+            }
+            if let Some(path) = var("CO2MON_PATH")? {
+                let path = CString::new(path).map_err(|_| Error::InvalidEnvVar("CO2MON_PATH"))?;
+                options.with_path(path);
+            }
+            if let Some(ids) = var("CO2MON_VID_PID")? {
+                let (vid, pid) = ids
+                    .split_once(':')
+                    .and_then(|(vid, pid)| {
+                        let vid = u16::from_str_radix(vid, 16).ok()?;
+                        let pid = u16::from_str_radix(pid, 16).ok()?;
+                        Some((vid, pid))
+                    })
+                    .ok_or(Error::InvalidEnvVar("CO2MON_VID_PID"))?;
+                options.with_vid_pid(vid, pid);
+            }
+        }
+
+        if let Some(key) = var("CO2MON_KEY")? {
+            if key == "random" {
+                options.with_random_key();
+            } else {
+                let key = parse_key(&key).ok_or(Error::InvalidEnvVar("CO2MON_KEY"))?;
+                options.with_key(key);
+            }
+        }
+        if let Some(timeout) = var("CO2MON_TIMEOUT_MS")? {
+            let timeout = match timeout.as_str() {
+                "none" => None,
+                millis => Some(Duration::from_millis(
+                    millis
+                        .parse()
+                        .map_err(|_| Error::InvalidEnvVar("CO2MON_TIMEOUT_MS"))?,
+                )),
+            };
+            options.timeout(timeout);
+        }
+        Ok(options)
+    }
+
+    /// Sets the serial number of the sensor device to open.
+    ///
+    /// The serial number appears to be the firmware version.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use co2mon::OpenOptions;
+    /// # use std::error::Error;
+    /// # use std::ffi::CString;
+    /// # use std::result::Result;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// let sensor = OpenOptions::new()
+    ///     .with_serial_number("1.40")
+    ///     .open()?;
+    /// #
+    /// # Ok(())
+    /// # }
+    #[cfg(feature = "hid")]
+    pub fn with_serial_number<S: Into<String>>(&mut self, sn: S) -> &mut Self {
+        self.path_type = DevicePathType::SerialNumber(sn.into());
+        self
+    }
+
+    /// Sets the path to the sensor device to open.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use co2mon::OpenOptions;
+    /// # use std::error::Error;
+    /// # use std::ffi::CString;
+    /// # use std::result::Result;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// let sensor = OpenOptions::new()
+    ///     .with_path(CString::new("/dev/bus/usb/001/004")?)
+    ///     .open()?;
+    /// #
+    /// # Ok(())
+    /// # }
+    #[cfg(feature = "hid")]
+    pub fn with_path(&mut self, path: CString) -> &mut Self {
+        self.path_type = DevicePathType::Path(path);
+        self
+    }
+
+    /// Sets the physical location of the sensor device to open: the USB bus
+    /// number and the chain of hub ports it is connected to.
+    ///
+    /// Unlike the serial number, which is the same on many devices, this
+    /// tells identical sensors apart for as long as they stay plugged into
+    /// the same ports. On Linux, the location appears as e.g. `1-2.3` (bus
+    /// 1, port 3 of the hub on port 2) in the kernel log. See also
+    /// [`DeviceInfo::usb_location`].
+    ///
+    /// This is only supported with the `libusb` and `hidraw` backends; on
+    /// other platforms, no device will be found. An empty list of ports is
+    /// rejected with [`Error::InvalidUsbLocation`] when opening the sensor.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use co2mon::{OpenOptions, Result};
+    /// # fn main() -> Result<()> {
+    /// #
+    /// let sensor = OpenOptions::new()
+    ///     .with_usb_location(1, &[2, 3])
+    ///     .open()?;
+    /// #
+    /// # Ok(())
+    /// # }
+    #[cfg(feature = "hid")]
+    pub fn with_usb_location(&mut self, bus: u8, ports: &[u8]) -> &mut Self {
+        self.path_type = DevicePathType::UsbLocation(bus, ports.to_vec());
+        self
+    }
+
+    /// Sets the USB Vendor ID and Product ID of the device to open, for
+    /// the rebranded sensors that use different ones.
+    ///
+    /// This doesn't apply to devices opened using
+    /// [`OpenOptions::with_path`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use co2mon::{OpenOptions, Result};
+    /// # fn main() -> Result<()> {
+    /// #
+    /// let sensor = OpenOptions::new().with_vid_pid(0x04d9, 0xa052).open()?;
+    /// #
+    /// # Ok(())
+    /// # }
+    #[cfg(feature = "hid")]
+    pub fn with_vid_pid(&mut self, vid: u16, pid: u16) -> &mut Self {
+        self.vendor_id = vid;
+        self.product_ids = vec![pid];
+        self
+    }
+
+    /// Sets a list of USB Product IDs to try, in order.
+    ///
+    /// A device with the first Product ID in the list is preferred, then one
+    /// with the second one, and so on. An empty list is rejected with
+    /// [`Error::InvalidProductIds`] when opening the sensor.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use co2mon::{OpenOptions, Result};
+    /// # fn main() -> Result<()> {
+    /// #
+    /// let sensor = OpenOptions::new()
+    ///     .with_product_ids(&[0xa052, 0x1234])
+    ///     .open()?;
+    /// #
+    /// # Ok(())
+    /// # }
+    #[cfg(feature = "hid")]
+    pub fn with_product_ids(&mut self, pids: &[u16]) -> &mut Self {
+        self.product_ids = pids.to_vec();
+        self
     }
 
     /// Sets the encryption key.
     ///
-    /// The key is used to encrypt the communication with the sensor, but
-    /// changing it is probably not very useful.
+    /// The key is used to encrypt the communication with the sensor, but
+    /// changing it is probably not very useful.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use co2mon::{OpenOptions, Result};
+    /// # use std::ffi::CString;
+    /// # #[cfg(feature = "hid")]
+    /// # fn main() -> Result<()> {
+    /// #
+    /// let sensor = OpenOptions::new()
+    ///     .with_key([0x62, 0xea, 0x1d, 0x4f, 0x14, 0xfa, 0xe5, 0x6c])
+    ///     .open()?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "hid"))]
+    /// # fn main() {}
+    pub fn with_key(&mut self, key: [u8; 8]) -> &mut Self {
+        self.key_type = KeyType::Fixed(key);
+        self
+    }
+
+    /// Uses a new, randomly-generated encryption key every time the sensor
+    /// is opened.
+    ///
+    /// On Unix, the key is read from `/dev/urandom`. Elsewhere, or if that
+    /// fails, it is derived from the standard library's hash seeds and the
+    /// current time using SipHash, which is not a cryptographic random number
+    /// generator, so that key should be considered predictable. Either way,
+    /// the encryption done by the device is too weak to protect the readings.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use co2mon::{OpenOptions, Result};
+    /// # #[cfg(feature = "hid")]
+    /// # fn main() -> Result<()> {
+    /// #
+    /// let sensor = OpenOptions::new().with_random_key().open()?;
+    /// println!("{:02x?}", sensor.key());
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "hid"))]
+    /// # fn main() {}
+    pub fn with_random_key(&mut self) -> &mut Self {
+        self.key_type = KeyType::Random;
+        self
+    }
+
+    /// Sets the read timeout.
+    ///
+    /// The timeout is stored in milliseconds, and `None` means that the reads
+    /// never time out. A zero timeout, or one shorter than a millisecond,
+    /// used to make the reads non-blocking, but is now rejected when opening
+    /// the sensor.
     ///
     /// # Example
     ///
     /// ```no_run
     /// # use co2mon::{OpenOptions, Result};
-    /// # use std::ffi::CString;
+    /// # use std::time::Duration;
+    /// # #[cfg(feature = "hid")]
     /// # fn main() -> Result<()> {
     /// #
     /// let sensor = OpenOptions::new()
-    ///     .with_key([0x62, 0xea, 0x1d, 0x4f, 0x14, 0xfa, 0xe5, 0x6c])
+    ///     .timeout(Some(Duration::from_secs(10)))
     ///     .open()?;
     /// #
     /// # Ok(())
     /// # }
-    pub fn with_key(&mut self, key: [u8; 8]) -> &mut Self {
-        self.key = key;
+    /// # #[cfg(not(feature = "hid"))]
+    /// # fn main() {}
+    pub fn timeout(&mut self, timeout: Option<Duration>) -> &mut Self {
+        self.timeout = timeout;
         self
     }
 
-    /// Sets the read timeout.
+    /// Sets the way [`Sensor::read`] combines the readings.
+    ///
+    /// The default is [`ReadStrategy::Fresh`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use co2mon::{OpenOptions, ReadStrategy, Result};
+    /// # #[cfg(feature = "hid")]
+    /// # fn main() -> Result<()> {
+    /// #
+    /// let sensor = OpenOptions::new()
+    ///     .read_strategy(ReadStrategy::Latest)
+    ///     .open()?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "hid"))]
+    /// # fn main() {}
+    pub fn read_strategy(&mut self, read_strategy: ReadStrategy) -> &mut Self {
+        self.read_strategy = read_strategy;
+        self
+    }
+
+    /// Makes the sensor send the encryption key again after `timeouts` single
+    /// reads in a row timed out, or `None` to disable this.
+    ///
+    /// Some devices stop sending reports until they receive the key again,
+    /// see [`Sensor::reinitialize`]. The default is `None`.
     ///
     /// # Example
     ///
     /// ```no_run
     /// # use co2mon::{OpenOptions, Result};
     /// # use std::time::Duration;
+    /// # #[cfg(feature = "hid")]
     /// # fn main() -> Result<()> {
     /// #
     /// let sensor = OpenOptions::new()
-    ///     .timeout(Some(Duration::from_secs(10)))
+    ///     .timeout(Some(Duration::from_secs(5)))
+    ///     .reinitialize_after(Some(3))
     ///     .open()?;
     /// #
     /// # Ok(())
     /// # }
-    pub fn timeout(&mut self, timeout: Option<Duration>) -> &mut Self {
-        self.timeout = timeout;
+    /// # #[cfg(not(feature = "hid"))]
+    /// # fn main() {}
+    pub fn reinitialize_after(&mut self, timeouts: Option<u32>) -> &mut Self {
+        self.reinitialize_after = timeouts;
+        self
+    }
+
+    /// Makes the sensor reopen the device when a read fails with
+    /// [`Error::Disconnected`] or another USB or I/O error.
+    ///
+    /// The reads wait until the device was reopened, as in
+    /// [`Sensor::reconnect`], and then continue. They only fail if the
+    /// policy gives up, with the error of the last attempt. The waiting time
+    /// doesn't count towards the timeout.
+    ///
+    /// Every successful attempt increments [`Stats::reconnects`], which can
+    /// be used to tell that a reconnect happened, e.g. to reset any state
+    /// kept about the device. It has no effect on sensors that were not
+    /// opened from a USB device.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use co2mon::{OpenOptions, ReconnectPolicy, Result};
+    /// # fn main() -> Result<()> {
+    /// #
+    /// let sensor = OpenOptions::new()
+    ///     .auto_reconnect(ReconnectPolicy::new())
+    ///     .open()?;
+    /// let mut reconnects = 0;
+    /// loop {
+    ///     let reading = sensor.read()?;
+    ///     if sensor.stats().reconnects() != reconnects {
+    ///         reconnects = sensor.stats().reconnects();
+    ///         println!("reconnected");
+    ///     }
+    ///     println!("{} ppm CO₂", reading.co2());
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    #[cfg(feature = "hid")]
+    pub fn auto_reconnect(&mut self, policy: ReconnectPolicy) -> &mut Self {
+        self.auto_reconnect = Some(policy);
         self
     }
 
     /// Opens the sensor.
     ///
+    /// # Errors
+    ///
+    /// The options are checked before accessing the device, and
+    /// [`Error::InvalidSerialNumber`], [`Error::InvalidPath`],
+    /// [`Error::InvalidUsbLocation`], [`Error::InvalidProductIds`] or
+    /// [`Error::InvalidTimeout`] will be returned if they are not valid.
+    /// [`Error::DeviceNotFound`] will be returned if no device matches them,
+    /// and [`Error::PermissionDenied`] if the user is not allowed to open it.
+    /// Otherwise, an error will be returned if the device could not be opened.
+    ///
     /// # Example
     ///
     /// ```no_run
@@ -478,13 +2600,102 @@ impl OpenOptions {
     /// #
     /// # Ok(())
     /// # }
+    #[cfg(feature = "hid")]
     pub fn open(&self) -> Result<Sensor> {
         Sensor::open(self)
     }
+
+    /// Opens every matching sensor.
+    ///
+    /// When a serial number or path is set, only the devices that match it
+    /// are opened. Each sensor uses a new key if
+    /// [`OpenOptions::with_random_key`] was called.
+    ///
+    /// # Errors
+    ///
+    /// The options are checked before accessing the devices, like in
+    /// [`OpenOptions::open`]. An error will be returned if any of the devices
+    /// could not be opened.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use co2mon::{OpenOptions, Result};
+    /// # fn main() -> Result<()> {
+    /// #
+    /// for sensor in OpenOptions::new().open_all()? {
+    ///     let info = sensor.device_info().unwrap();
+    ///     println!("{:?}: {} ppm CO₂", info.path(), sensor.read()?.co2());
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    #[cfg(feature = "hid")]
+    pub fn open_all(&self) -> Result<Vec<Sensor>> {
+        Sensor::open_all(self)
+    }
+
+    /// Uses a byte stream as the sensor device.
+    ///
+    /// This is meant for the bare ZG modules connected through a USB-UART
+    /// adapter, which send unencrypted 5-byte frames instead of HID reports.
+    /// The stream is usually a serial port, set up by the caller (e.g. using
+    /// `stty` or the [`serialport`][serialport] crate).
+    ///
+    /// The device selection and the encryption key are ignored. The timeout
+    /// still applies to [`Sensor::read`], but single reads are only limited
+    /// by the timeout of the stream itself, which should report expiry as
+    /// an [`ErrorKind::TimedOut`][std::io::ErrorKind::TimedOut] or
+    /// [`ErrorKind::WouldBlock`][std::io::ErrorKind::WouldBlock] error.
+    ///
+    /// [serialport]: https://docs.rs/serialport/
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use co2mon::OpenOptions;
+    /// # use std::error::Error;
+    /// # use std::fs::File;
+    /// # use std::result::Result;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// let port = File::open("/dev/ttyUSB0")?;
+    /// let sensor = OpenOptions::new().open_stream(port)?;
+    /// let reading = sensor.read()?;
+    /// #
+    /// # Ok(())
+    /// # }
+    pub fn open_stream<R: Read + Send + 'static>(&self, stream: R) -> Result<Sensor> {
+        self.open_backend(StreamBackend::new(stream))
+    }
+
+    /// Opens the sensor using a custom [`Backend`].
+    ///
+    /// The device selection is ignored, but the feature report with the
+    /// encryption key is sent to the backend as it would be to a USB device.
+    ///
+    /// # Example
+    ///
+    /// See [`Backend`].
+    pub fn open_backend<B: Backend + 'static>(&self, backend: B) -> Result<Sensor> {
+        Sensor::with_backend(Box::new(backend), self)
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::{
+        Backend, Error, OpenOptions, ReadStrategy, Reading, Result, Sensor, SingleReading, Stats,
+    };
+    use crate::fixtures::{fake_clock, CO2, TEMPERATURE};
+    use crate::mock::MockBackend;
+    use std::collections::VecDeque;
+    use std::convert::TryFrom;
+    use std::io;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Barrier, Mutex};
+    use std::time::{Duration, Instant};
+
     #[test]
     fn test_decrypt() {
         let data = [0x6c, 0xa4, 0xa2, 0xb6, 0x5d, 0x9a, 0x9c, 0x08];
@@ -494,6 +2705,792 @@ mod tests {
         assert_eq!(data, [0x50, 0x04, 0x57, 0xab, 0x0d, 0x00, 0x00, 0x00]);
     }
 
+    struct FakeDevice {
+        feature_reports: Mutex<Vec<Vec<u8>>>,
+        timeouts: Mutex<Vec<i32>>,
+        reads: Mutex<VecDeque<Result<Vec<u8>>>>,
+    }
+
+    impl FakeDevice {
+        fn new(reads: Vec<Result<Vec<u8>>>) -> Self {
+            Self {
+                feature_reports: Mutex::new(Vec::new()),
+                timeouts: Mutex::new(Vec::new()),
+                reads: Mutex::new(reads.into()),
+            }
+        }
+    }
+
+    impl Backend for Arc<FakeDevice> {
+        fn send_feature_report(&self, data: &[u8]) -> Result<()> {
+            self.feature_reports.lock().unwrap().push(data.to_vec());
+            Ok(())
+        }
+
+        fn read_timeout(&self, buf: &mut [u8], timeout: i32) -> Result<usize> {
+            self.timeouts.lock().unwrap().push(timeout);
+            match self.reads.lock().unwrap().pop_front() {
+                Some(Ok(data)) => {
+                    buf[..data.len()].copy_from_slice(&data);
+                    Ok(data.len())
+                }
+                Some(Err(e)) => Err(e),
+                None => Err(io::Error::from(io::ErrorKind::BrokenPipe).into()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_open_backend() {
+        let device = Arc::new(FakeDevice::new(vec![
+            Ok(CO2.to_vec()),
+            Ok(CO2[..5].to_vec()),
+            Ok(Vec::new()),
+            Err(io::Error::from(io::ErrorKind::BrokenPipe).into()),
+        ]));
+        let sensor = OpenOptions::new()
+            .with_key([1, 2, 3, 4, 5, 6, 7, 8])
+            .open_backend(device.clone())
+            .unwrap();
+        assert_eq!(
+            *device.feature_reports.lock().unwrap(),
+            [vec![0, 1, 2, 3, 4, 5, 6, 7, 8]]
+        );
+
+        assert_eq!(sensor.read_one().unwrap(), SingleReading::CO2(1111));
+        // partial read
+        assert!(matches!(sensor.read_one(), Err(Error::InvalidMessage)));
+        // timeout
+        assert!(matches!(sensor.read_one(), Err(Error::Timeout)));
+        // disconnect
+        assert!(matches!(sensor.read_one(), Err(Error::Io(_))));
+    }
+
+    #[test]
+    fn test_read() {
+        let device = Arc::new(FakeDevice::new(vec![
+            Ok(CO2.to_vec()),
+            Ok(vec![0x41, 0x00, 0x00, 0x41, 0x0d, 0x00, 0x00, 0x00]),
+            Ok(TEMPERATURE.to_vec()),
+        ]));
+        let sensor = OpenOptions::new().open_backend(device).unwrap();
+        let reading = sensor.read().unwrap();
+        assert_eq!(reading.co2(), 1111);
+        assert_eq!(reading.temperature(), 4713.0 * 0.0625 - 273.15);
+
+        // the error is passed through
+        assert!(matches!(sensor.read(), Err(Error::Io(_))));
+    }
+
+    #[test]
+    fn test_read_raw() {
+        let device = Arc::new(FakeDevice::new(vec![
+            Ok(CO2.to_vec()),
+            Ok(Vec::new()),
+            Ok(CO2[..5].to_vec()),
+        ]));
+        let sensor = OpenOptions::new().open_backend(device.clone()).unwrap();
+        let timeout = Duration::from_micros(1500);
+        assert_eq!(sensor.read_raw(timeout).unwrap(), Some(CO2));
+        assert_eq!(sensor.read_raw(timeout).unwrap(), None);
+        assert!(matches!(
+            sensor.read_raw(timeout),
+            Err(Error::InvalidMessage)
+        ));
+        // the timeout is rounded up
+        assert_eq!(*device.timeouts.lock().unwrap(), [2, 2, 2]);
+        assert_eq!(sensor.stats(), Stats::default());
+    }
+
+    #[test]
+    fn test_read_timeout() {
+        let device = Arc::new(FakeDevice::new((0..10).map(|_| Ok(CO2.to_vec())).collect()));
+        let mut sensor = OpenOptions::new()
+            .timeout(Some(Duration::from_secs(5)))
+            .open_backend(device.clone())
+            .unwrap();
+        sensor.clock = fake_clock(Duration::from_secs(1));
+        assert!(matches!(sensor.read(), Err(Error::Timeout)));
+        // three clock readings per report (the remaining time, and the start
+        // and the end of the read), plus the start time
+        assert_eq!(device.reads.lock().unwrap().len(), 8);
+        // the remaining time is passed to the device
+        assert_eq!(*device.timeouts.lock().unwrap(), [4000, 1000]);
+
+        let device = Arc::new(FakeDevice::new((0..10).map(|_| Ok(CO2.to_vec())).collect()));
+        let mut sensor = OpenOptions::new()
+            .timeout(None)
+            .open_backend(device)
+            .unwrap();
+        sensor.clock = fake_clock(Duration::from_secs(3600));
+        // only stops at the end of the script
+        assert!(matches!(sensor.read(), Err(Error::Io(_))));
+    }
+
+    #[test]
+    fn test_read_many() {
+        /// A device which only sends its first report once all the others
+        /// are waiting for theirs.
+        struct SyncedDevice {
+            backend: MockBackend,
+            barrier: Arc<Barrier>,
+            waited: AtomicBool,
+        }
+
+        impl Backend for SyncedDevice {
+            fn send_feature_report(&self, data: &[u8]) -> Result<()> {
+                self.backend.send_feature_report(data)
+            }
+
+            fn read_timeout(&self, buf: &mut [u8], timeout: i32) -> Result<usize> {
+                // this never returns if the sensors are read one after another
+                if !self.waited.swap(true, Ordering::SeqCst) {
+                    self.barrier.wait();
+                }
+                self.backend.read_timeout(buf, timeout)
+            }
+        }
+
+        let barrier = Arc::new(Barrier::new(5));
+        let mut sensors = (0..5)
+            .map(|i| {
+                let reports = if i < 4 {
+                    vec![CO2, TEMPERATURE]
+                } else {
+                    // this one never sends the temperature
+                    vec![CO2]
+                };
+                let device = SyncedDevice {
+                    backend: MockBackend::new(reports),
+                    barrier: barrier.clone(),
+                    waited: AtomicBool::new(false),
+                };
+                OpenOptions::new().open_backend(device).unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let start = Instant::now();
+        let readings = Sensor::read_many(&mut sensors, start + Duration::from_secs(1));
+        assert_eq!(readings.len(), 5);
+        for reading in &readings[..4] {
+            assert_eq!(reading.as_ref().unwrap().co2(), 1111);
+        }
+        assert!(matches!(readings[4], Err(Error::Timeout)));
+
+        assert!(Sensor::read_many(&mut [], start).is_empty());
+    }
+
+    #[test]
+    fn test_read_until() {
+        let device = Arc::new(FakeDevice::new(vec![
+            Ok(CO2.to_vec()),
+            Ok(Vec::new()),
+            Ok(TEMPERATURE.to_vec()),
+        ]));
+        let mut sensor = OpenOptions::new().open_backend(device.clone()).unwrap();
+        sensor.clock = fake_clock(Duration::from_millis(100));
+        let start = sensor.clock.now();
+
+        // already expired
+        assert!(matches!(sensor.read_until(start), Err(Error::Timeout)));
+        assert!(device.timeouts.lock().unwrap().is_empty());
+
+        let deadline = start + Duration::from_millis(1050);
+        assert_eq!(
+            sensor.read_one_until(deadline).unwrap(),
+            SingleReading::CO2(1111)
+        );
+        assert!(matches!(
+            sensor.read_one_until(deadline),
+            Err(Error::Timeout)
+        ));
+        // the temperature arrives, but the device is gone before the CO₂
+        assert!(matches!(sensor.read_until(deadline), Err(Error::Io(_))));
+        assert_eq!(*device.timeouts.lock().unwrap(), [850, 550, 350, 50]);
+    }
+
+    #[test]
+    fn test_reading_try_from() {
+        let readings = [
+            SingleReading::Temperature(20.0),
+            SingleReading::Humidity(45.0),
+            SingleReading::CO2(500),
+            SingleReading::Temperature(21.5),
+        ];
+        let reading = Reading::try_from(&readings[..]).unwrap();
+        assert_eq!(reading.co2(), 500);
+        assert_eq!(reading.temperature(), 21.5);
+
+        assert!(matches!(
+            Reading::try_from(&readings[..2]),
+            Err(Error::InvalidMessage)
+        ));
+        assert!(Reading::try_from(&[][..]).is_err());
+    }
+
+    #[test]
+    fn test_stats() {
+        let mut bad_checksum = CO2;
+        bad_checksum[3] ^= 1;
+        let device = Arc::new(FakeDevice::new(vec![
+            Ok(CO2.to_vec()),
+            Ok(Vec::new()),
+            Ok(bad_checksum.to_vec()),
+            Ok(CO2[..4].to_vec()),
+            Ok(TEMPERATURE.to_vec()),
+        ]));
+        let sensor = OpenOptions::new().open_backend(device).unwrap();
+        assert_eq!(sensor.stats(), Stats::default());
+
+        for _ in 0..6 {
+            let _ = sensor.read_one();
+        }
+        let stats = sensor.stats();
+        assert_eq!(stats.frames(), 3);
+        assert_eq!(stats.timeouts(), 1);
+        assert_eq!(stats.checksum_errors(), 1);
+        assert_eq!(stats.invalid_messages(), 1);
+    }
+
+    #[test]
+    fn test_latencies() {
+        let device = Arc::new(FakeDevice::new(vec![
+            Ok(CO2.to_vec()),
+            Ok(Vec::new()),
+            Ok(TEMPERATURE.to_vec()),
+        ]));
+        let mut sensor = OpenOptions::new().open_backend(device).unwrap();
+        // each read takes one step of the clock
+        sensor.clock = fake_clock(Duration::from_millis(200));
+        for _ in 0..3 {
+            let _ = sensor.read_one();
+        }
+        // the timeout isn't counted
+        assert_eq!(sensor.stats().latencies(), [0, 0, 2, 0, 0, 0]);
+
+        let device = Arc::new(FakeDevice::new(vec![Ok(CO2.to_vec())]));
+        let mut sensor = OpenOptions::new().open_backend(device).unwrap();
+        sensor.clock = fake_clock(Duration::from_secs(10));
+        sensor.read_one().unwrap();
+        assert_eq!(sensor.stats().latencies(), [0, 0, 0, 0, 0, 1]);
+    }
+
+    #[test]
+    fn test_read_value() {
+        let device = Arc::new(FakeDevice::new(vec![
+            Ok(CO2.to_vec()),
+            Ok(TEMPERATURE.to_vec()),
+            Ok(CO2.to_vec()),
+        ]));
+        let mut sensor = OpenOptions::new().open_backend(device.clone()).unwrap();
+        sensor.clock = fake_clock(Duration::from_secs(1));
+        assert!((sensor.read_temperature().unwrap() - 21.4125).abs() < 1e-4);
+        assert_eq!(sensor.read_co2().unwrap(), 1111);
+        assert!(device.reads.lock().unwrap().is_empty());
+
+        let reads = (0..10).map(|_| Ok(CO2.to_vec())).collect();
+        let device = Arc::new(FakeDevice::new(reads));
+        let mut sensor = OpenOptions::new().open_backend(device.clone()).unwrap();
+        sensor.clock = fake_clock(Duration::from_secs(1));
+        assert!(matches!(sensor.read_humidity(), Err(Error::Timeout)));
+        assert_eq!(device.reads.lock().unwrap().len(), 8);
+    }
+
+    #[test]
+    fn test_humidity_supported() {
+        const NO_HUMIDITY: [u8; 8] = [0x41, 0x00, 0x00, 0x41, 0x0d, 0x00, 0x00, 0x00];
+        const HUMIDITY: [u8; 8] = [0x41, 0x11, 0x94, 0xe6, 0x0d, 0x00, 0x00, 0x00];
+
+        let mut reads = vec![Ok(CO2.to_vec())];
+        reads.extend((0..5).map(|_| Ok(NO_HUMIDITY.to_vec())));
+        let device = Arc::new(FakeDevice::new(reads));
+        let sensor = OpenOptions::new().open_backend(device.clone()).unwrap();
+        assert_eq!(sensor.humidity_supported(), None);
+        assert_eq!(sensor.read_humidity().unwrap(), None);
+        assert_eq!(sensor.humidity_supported(), Some(false));
+        assert!(device.reads.lock().unwrap().is_empty());
+        // no further reads are needed
+        assert_eq!(sensor.read_humidity().unwrap(), None);
+
+        let device = Arc::new(FakeDevice::new(vec![
+            Ok(NO_HUMIDITY.to_vec()),
+            Ok(HUMIDITY.to_vec()),
+            Ok(NO_HUMIDITY.to_vec()),
+        ]));
+        let sensor = OpenOptions::new().open_backend(device).unwrap();
+        assert_eq!(sensor.read_humidity().unwrap(), Some(45.0));
+        assert_eq!(sensor.humidity_supported(), Some(true));
+        assert_eq!(sensor.read_humidity().unwrap(), Some(0.0));
+    }
+
+    #[test]
+    fn test_capabilities() {
+        let device = Arc::new(FakeDevice::new(vec![
+            Ok(CO2.to_vec()),
+            Ok(TEMPERATURE.to_vec()),
+            Ok(CO2[..4].to_vec()),
+            Ok(CO2.to_vec()),
+            Ok(Vec::new()),
+        ]));
+        let mut sensor = OpenOptions::new().open_backend(device.clone()).unwrap();
+        sensor.clock = fake_clock(Duration::from_secs(1));
+        assert_eq!(sensor.read_co2().unwrap(), 1111);
+
+        let capabilities = sensor.capabilities(Duration::from_secs(10)).unwrap();
+        assert!(capabilities.co2());
+        assert!(capabilities.temperature());
+        assert!(!capabilities.humidity());
+        assert!(device.reads.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_read_all() {
+        const HUMIDITY: [u8; 8] = [0x41, 0x11, 0x94, 0xe6, 0x0d, 0x00, 0x00, 0x00];
+        const UNKNOWN: [u8; 8] = [0x6d, 0x12, 0x34, 0xb3, 0x0d, 0x00, 0x00, 0x00];
+
+        let device = Arc::new(FakeDevice::new(vec![
+            Ok(HUMIDITY.to_vec()),
+            Ok(UNKNOWN.to_vec()),
+            Ok(CO2.to_vec()),
+            Ok(TEMPERATURE.to_vec()),
+            Ok(HUMIDITY.to_vec()),
+        ]));
+        let sensor = OpenOptions::new().open_backend(device.clone()).unwrap();
+        let reading = sensor.read_all().unwrap();
+        assert_eq!(reading.co2(), 1111);
+        assert!((reading.temperature() - 21.4125).abs() < 1e-4);
+        assert_eq!(reading.humidity(), Some(45.0));
+        assert_eq!(reading.other(), [(0x6d, 0x1234)]);
+        assert!(device.reads.lock().unwrap().is_empty());
+
+        // the reading which ended the cycle starts the next one
+        let cycle = [HUMIDITY, UNKNOWN, CO2, TEMPERATURE];
+        let reads = cycle.iter().chain(&cycle).chain(&[HUMIDITY]);
+        let device = Arc::new(FakeDevice::new(
+            reads.map(|report| Ok(report.to_vec())).collect(),
+        ));
+        let sensor = OpenOptions::new().open_backend(device.clone()).unwrap();
+        sensor.read_all().unwrap();
+        let reading = sensor.read_all().unwrap();
+        assert_eq!(reading.humidity(), Some(45.0));
+        assert_eq!(reading.other(), [(0x6d, 0x1234)]);
+        assert!(device.reads.lock().unwrap().is_empty());
+
+        // the cycle isn't over before both values are available
+        let device = Arc::new(FakeDevice::new(vec![
+            Ok(CO2.to_vec()),
+            Ok(CO2.to_vec()),
+            Ok(TEMPERATURE.to_vec()),
+            Ok(CO2.to_vec()),
+        ]));
+        let sensor = OpenOptions::new().open_backend(device.clone()).unwrap();
+        let reading = sensor.read_all().unwrap();
+        assert_eq!(reading.humidity(), None);
+        assert!(reading.other().is_empty());
+        assert!(device.reads.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_read_strategy() {
+        // returns the number of frames left after two reads
+        let remaining = |strategy, second: [[u8; 8]; 2]| {
+            let mut reads = vec![Ok(CO2.to_vec()), Ok(TEMPERATURE.to_vec())];
+            reads.extend(second.iter().map(|report| Ok(report.to_vec())));
+            let device = Arc::new(FakeDevice::new(reads));
+            let sensor = OpenOptions::new()
+                .read_strategy(strategy)
+                .open_backend(device.clone())
+                .unwrap();
+            sensor.read().unwrap();
+            sensor.read().unwrap();
+            let remaining = device.reads.lock().unwrap().len();
+            remaining
+        };
+        assert_eq!(remaining(ReadStrategy::Fresh, [CO2, TEMPERATURE]), 0);
+        assert_eq!(remaining(ReadStrategy::FreshCo2, [CO2, TEMPERATURE]), 1);
+        assert_eq!(remaining(ReadStrategy::FreshCo2, [TEMPERATURE, CO2]), 0);
+        assert_eq!(remaining(ReadStrategy::Latest, [TEMPERATURE, CO2]), 1);
+    }
+
+    #[test]
+    fn test_display() {
+        let reading = Reading {
+            temperature: 21.4125,
+            co2: 950,
+        };
+        assert_eq!(reading.to_string(), "21.4 °C, 950 ppm CO₂");
+        assert_eq!(format!("{:#}", reading), "temperature=21.4125 co2=950");
+        assert_eq!(format!("{:.0}", reading), "21 °C, 950 ppm CO₂");
+        assert_eq!(format!("{:#.2}", reading), "temperature=21.41 co2=950");
+    }
+
+    #[test]
+    fn test_temperature_units() {
+        for ticks in [0, 0x1269, 0x1280, u16::MAX] {
+            let reading = Reading {
+                temperature: zg_co2::ticks_to_celsius(ticks),
+                co2: 400,
+            };
+            assert_eq!(reading.temperature_raw(), ticks);
+        }
+
+        let reading = Reading {
+            temperature: 25.0,
+            co2: 400,
+        };
+        assert_eq!(reading.temperature_fahrenheit(), 77.0);
+        assert_eq!(reading.temperature_kelvin(), 298.15);
+    }
+
+    #[test]
+    fn test_reinitialize_after() {
+        let device = Arc::new(FakeDevice::new(vec![
+            Ok(Vec::new()),
+            Ok(CO2.to_vec()),
+            Ok(Vec::new()),
+            Ok(Vec::new()),
+            Ok(Vec::new()),
+        ]));
+        let sensor = OpenOptions::new()
+            .reinitialize_after(Some(2))
+            .open_backend(device.clone())
+            .unwrap();
+        let sent = || device.feature_reports.lock().unwrap().len();
+        assert_eq!(sent(), 1);
+
+        // the count is reset by a successful read
+        assert!(sensor.read_one().is_err());
+        assert!(sensor.read_one().is_ok());
+        assert!(sensor.read_one().is_err());
+        assert_eq!(sent(), 1);
+        assert!(sensor.read_one().is_err());
+        assert_eq!(sent(), 2);
+
+        sensor.reinitialize().unwrap();
+        assert_eq!(sent(), 3);
+        assert!(sensor.read_one().is_err());
+        assert_eq!(sent(), 3);
+    }
+
+    #[test]
+    fn test_from_env() {
+        use std::collections::HashMap;
+        use std::ffi::OsString;
+
+        let from_vars = |vars: &[(&'static str, &str)]| {
+            let vars = vars
+                .iter()
+                .map(|&(name, value)| (name, OsString::from(value)))
+                .collect::<HashMap<_, _>>();
+            OpenOptions::from_vars(|name| vars.get(name).cloned())
+        };
+
+        let options = from_vars(&[]).unwrap();
+        assert_eq!(options.timeout, Some(Duration::from_secs(5)));
+
+        let options = from_vars(&[
+            ("CO2MON_KEY", "62ea1d4f14fae56c"),
+            ("CO2MON_TIMEOUT_MS", "1500"),
+        ])
+        .unwrap();
+        assert!(matches!(
+            options.key_type,
+            super::KeyType::Fixed([0x62, 0xea, 0x1d, 0x4f, 0x14, 0xfa, 0xe5, 0x6c])
+        ));
+        assert_eq!(options.timeout, Some(Duration::from_millis(1500)));
+
+        let options =
+            from_vars(&[("CO2MON_KEY", "random"), ("CO2MON_TIMEOUT_MS", "none")]).unwrap();
+        assert!(matches!(options.key_type, super::KeyType::Random));
+        assert_eq!(options.timeout, None);
+
+        for &(name, value) in &[
+            ("CO2MON_KEY", "62ea1d4f"),
+            ("CO2MON_KEY", "62ea1d4f14fae5xx"),
+            ("CO2MON_TIMEOUT_MS", "5s"),
+        ] {
+            assert!(matches!(
+                from_vars(&[(name, value)]),
+                Err(Error::InvalidEnvVar(n)) if n == name
+            ));
+        }
+
+        #[cfg(feature = "hid")]
+        {
+            let options = from_vars(&[("CO2MON_VID_PID", "04d9:a052")]).unwrap();
+            assert_eq!(options.vendor_id, 0x04d9);
+            assert_eq!(options.product_ids, [0xa052]);
+            assert!(matches!(
+                from_vars(&[("CO2MON_VID_PID", "04d9")]),
+                Err(Error::InvalidEnvVar("CO2MON_VID_PID"))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_read_n() {
+        let device = Arc::new(FakeDevice::new(vec![
+            Ok(CO2.to_vec()),
+            Ok(TEMPERATURE.to_vec()),
+            Ok(CO2.to_vec()),
+            Ok(TEMPERATURE.to_vec()),
+            Ok(CO2.to_vec()),
+        ]));
+        let mut sensor = OpenOptions::new().open_backend(device).unwrap();
+        sensor.clock = fake_clock(Duration::from_secs(1));
+
+        let readings = sensor.read_one_n(2, Duration::from_secs(10)).unwrap();
+        assert_eq!(readings[0], SingleReading::CO2(1111));
+        assert_eq!(readings.len(), 2);
+
+        // the second reading doesn't arrive before the deadline
+        let err = sensor.read_n(2, Duration::from_secs(5)).unwrap_err();
+        assert_eq!(err.readings().len(), 1);
+        assert!(matches!(err.error(), Error::Timeout));
+    }
+
+    #[test]
+    fn test_read_timestamped() {
+        let device = Arc::new(FakeDevice::new(vec![
+            Ok(CO2.to_vec()),
+            Ok(TEMPERATURE.to_vec()),
+            Ok(CO2.to_vec()),
+        ]));
+        let sensor = OpenOptions::new().open_backend(device).unwrap();
+        let before = Instant::now();
+        let first = sensor.read_one_timestamped().unwrap();
+        assert_eq!(*first.value(), SingleReading::CO2(1111));
+        assert!(first.instant() >= before);
+
+        let second = sensor.read_timestamped().unwrap();
+        assert_eq!(second.value().co2(), 1111);
+        assert!(second.instant() >= first.instant());
+        assert!(second.system_time() >= first.system_time());
+    }
+
+    #[test]
+    fn test_readings() {
+        let reads = (0..4)
+            .flat_map(|_| vec![Ok(CO2.to_vec()), Ok(TEMPERATURE.to_vec())])
+            .collect();
+        let device = Arc::new(FakeDevice::new(reads));
+        let sensor = OpenOptions::new().open_backend(device).unwrap();
+
+        let single = sensor.single_readings().take(2).collect::<Result<Vec<_>>>();
+        assert_eq!(single.unwrap()[0], SingleReading::CO2(1111));
+        assert_eq!(sensor.readings().take(3).filter(|r| r.is_ok()).count(), 3);
+        // the device is gone
+        assert!(sensor.readings().next().unwrap().is_err());
+    }
+
+    #[test]
+    fn test_read_timeout_override() {
+        let device = Arc::new(FakeDevice::new(vec![
+            Ok(CO2.to_vec()),
+            Ok(TEMPERATURE.to_vec()),
+            Ok(CO2.to_vec()),
+        ]));
+        let mut sensor = OpenOptions::new().open_backend(device.clone()).unwrap();
+        sensor.clock = fake_clock(Duration::from_millis(100));
+        sensor.read_timeout(Duration::from_secs(30)).unwrap();
+        sensor.read_one_timeout(Duration::from_secs(1)).unwrap();
+        assert_eq!(*device.timeouts.lock().unwrap(), [29900, 29600, 900]);
+        assert!(matches!(
+            sensor.read_one_timeout(Duration::ZERO),
+            Err(Error::Timeout)
+        ));
+    }
+
+    #[test]
+    fn test_read_timeout_max() {
+        let device = Arc::new(FakeDevice::new(vec![
+            Ok(CO2.to_vec()),
+            Ok(TEMPERATURE.to_vec()),
+            Ok(CO2.to_vec()),
+            Ok(CO2.to_vec()),
+        ]));
+        let sensor = OpenOptions::new().open_backend(device.clone()).unwrap();
+        // too far in the future for an `Instant`, so there is no deadline
+        sensor.read_timeout(Duration::MAX).unwrap();
+        sensor.read_one_timeout(Duration::MAX).unwrap();
+        assert!(sensor.read_one_n(1, Duration::MAX).is_ok());
+        assert_eq!(*device.timeouts.lock().unwrap(), [-1, -1, -1, -1]);
+    }
+
+    #[test]
+    fn test_reconnect() {
+        let device = Arc::new(FakeDevice::new(Vec::new()));
+        let mut sensor = OpenOptions::new().open_backend(device).unwrap();
+        assert!(matches!(sensor.reconnect(), Err(Error::Unsupported)));
+        assert_eq!(sensor.stats().reconnects(), 0);
+    }
+
+    #[cfg(feature = "hid")]
+    #[test]
+    fn test_auto_reconnect() {
+        use super::ReconnectPolicy;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let device = Arc::new(FakeDevice::new(vec![Ok(CO2.to_vec())]));
+        let mut sensor = OpenOptions::new()
+            .auto_reconnect(ReconnectPolicy::new().max_attempts(Some(3)))
+            .open_backend(device)
+            .unwrap();
+        let new_device = Arc::new(FakeDevice::new(vec![Ok(TEMPERATURE.to_vec())]));
+        let attempts = Arc::new(AtomicUsize::new(0));
+        sensor.reopen = Some(Box::new({
+            let new_device = new_device.clone();
+            let attempts = attempts.clone();
+            move || {
+                // the device is back on the second attempt
+                if attempts.fetch_add(1, Ordering::Relaxed) == 0 {
+                    Err(io::Error::from(io::ErrorKind::NotFound).into())
+                } else {
+                    let device: Box<dyn Backend> = Box::new(new_device.clone());
+                    Ok((device, None))
+                }
+            }
+        }));
+        sensor.clock = fake_clock(Duration::ZERO);
+
+        assert_eq!(sensor.read_one().unwrap(), SingleReading::CO2(1111));
+        assert!(matches!(
+            sensor.read_one().unwrap(),
+            SingleReading::Temperature(_)
+        ));
+        assert_eq!(attempts.load(Ordering::Relaxed), 2);
+        assert_eq!(sensor.stats().reconnects(), 1);
+        // the key was sent to the new device
+        assert_eq!(*new_device.feature_reports.lock().unwrap(), [vec![0; 9]]);
+
+        // the new device is gone too, and the policy gives up
+        attempts.store(0, Ordering::Relaxed);
+        sensor.reopen = Some(Box::new({
+            let attempts = attempts.clone();
+            move || {
+                attempts.fetch_add(1, Ordering::Relaxed);
+                Err(io::Error::from(io::ErrorKind::NotFound).into())
+            }
+        }));
+        assert!(matches!(sensor.read_one(), Err(Error::Io(_))));
+        assert_eq!(attempts.load(Ordering::Relaxed), 3);
+        assert_eq!(sensor.stats().reconnects(), 1);
+    }
+
+    #[test]
+    fn test_set_timeout() {
+        let device = Arc::new(FakeDevice::new(Vec::new()));
+        let mut sensor = OpenOptions::new().open_backend(device).unwrap();
+        assert_eq!(sensor.timeout, 5000);
+        assert_eq!(sensor.timeout(), Some(Duration::from_secs(5)));
+        sensor.set_timeout(None).unwrap();
+        assert_eq!(sensor.timeout, -1);
+        assert_eq!(sensor.timeout(), None);
+        sensor
+            .set_timeout(Some(Duration::from_millis(100)))
+            .unwrap();
+        assert_eq!(sensor.timeout, 100);
+        assert!(matches!(
+            sensor.set_timeout(Some(Duration::from_secs(1 << 40))),
+            Err(Error::InvalidTimeout)
+        ));
+        assert_eq!(sensor.timeout, 100);
+    }
+
+    #[test]
+    fn test_set_key() {
+        let key = [0x62, 0xea, 0x1d, 0x4f, 0x14, 0xfa, 0xe5, 0x6c];
+        // CO₂ reading, encrypted using the key above
+        let report = [0x71, 0xb0, 0xc0, 0xda, 0xb7, 0x7f, 0x66, 0x47];
+        let device = Arc::new(FakeDevice::new(vec![Ok(report.to_vec())]));
+        let mut sensor = OpenOptions::new().open_backend(device.clone()).unwrap();
+        assert_eq!(sensor.key(), [0; 8]);
+
+        sensor.set_key(key).unwrap();
+        assert_eq!(sensor.key(), key);
+        let feature_reports = device.feature_reports.lock().unwrap().clone();
+        assert_eq!(feature_reports.len(), 2);
+        assert_eq!(feature_reports[1][0], 0);
+        assert_eq!(feature_reports[1][1..], key);
+        assert_eq!(sensor.read_one().unwrap(), SingleReading::CO2(1111));
+    }
+
+    #[test]
+    fn test_random_key() {
+        let device = Arc::new(FakeDevice::new(Vec::new()));
+        let mut options = OpenOptions::new();
+        options.with_random_key();
+        let first = options.open_backend(device.clone()).unwrap();
+        let second = options.open_backend(device.clone()).unwrap();
+        assert_ne!(first.key(), second.key());
+
+        let feature_reports = device.feature_reports.lock().unwrap().clone();
+        assert_eq!(feature_reports[0][1..], first.key());
+        assert_eq!(feature_reports[1][1..], second.key());
+
+        let sensor = options.with_key([0; 8]).open_backend(device).unwrap();
+        assert_eq!(sensor.key(), [0; 8]);
+    }
+
+    #[test]
+    #[cfg(feature = "hid")]
+    fn test_validate() {
+        use std::ffi::CString;
+
+        assert!(OpenOptions::new().validate().is_ok());
+        assert!(matches!(
+            OpenOptions::new().with_serial_number("").validate(),
+            Err(Error::InvalidSerialNumber)
+        ));
+        assert!(matches!(
+            OpenOptions::new()
+                .with_path(CString::new("").unwrap())
+                .validate(),
+            Err(Error::InvalidPath)
+        ));
+        assert!(matches!(
+            OpenOptions::new().with_usb_location(1, &[]).validate(),
+            Err(Error::InvalidUsbLocation)
+        ));
+        assert!(matches!(
+            OpenOptions::new().with_product_ids(&[]).validate(),
+            Err(Error::InvalidProductIds)
+        ));
+        assert!(matches!(
+            OpenOptions::new()
+                .auto_reconnect(super::ReconnectPolicy::new().initial_delay(Duration::ZERO))
+                .validate(),
+            Err(Error::InvalidTimeout)
+        ));
+        assert!(matches!(
+            OpenOptions::new()
+                .timeout(Some(Duration::from_micros(500)))
+                .validate(),
+            Err(Error::InvalidTimeout)
+        ));
+        assert!(OpenOptions::new().timeout(None).validate().is_ok());
+    }
+
+    #[test]
+    fn test_open_stream() {
+        use std::io::Cursor;
+
+        // starts in the middle of a frame
+        let data = vec![
+            0xab, 0x0d, 0x50, 0x04, 0x57, 0xab, 0x0d, 0x42, 0x12, 0x69, 0xbd, 0x0d,
+        ];
+        let sensor = OpenOptions::new().open_stream(Cursor::new(data)).unwrap();
+        match sensor.read_one() {
+            Ok(SingleReading::CO2(val)) => assert_eq!(val, 1111),
+            _ => panic!(),
+        }
+        match sensor.read_one() {
+            Ok(SingleReading::Temperature(val)) => assert_eq!(val, 4713.0 * 0.0625 - 273.15),
+            _ => panic!(),
+        }
+        assert!(sensor.read_one().is_err());
+    }
+
     #[test]
     fn test_open_options_send() {
         fn assert_send<T: Send>() {}
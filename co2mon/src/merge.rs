@@ -0,0 +1,158 @@
+//! Combining the readings of several sensors into a single one.
+//!
+//! [`Merge`] keeps the latest reading of each sensor and returns their
+//! median, which is not thrown off by a minority of faulty sensors. Readings
+//! older than a configurable age are ignored, so a sensor that stopped
+//! responding doesn't hold the result back.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use co2mon::merge::Merge;
+//! # use co2mon::{OpenOptions, Result};
+//! # use std::time::{Duration, Instant};
+//! # #[cfg(feature = "hid")]
+//! # fn main() -> Result<()> {
+//! #
+//! let sensors = vec![
+//!     OpenOptions::new().with_serial_number("1.40").open()?,
+//!     OpenOptions::new().with_serial_number("1.41").open()?,
+//! ];
+//! let mut merge = Merge::new(sensors.len(), Duration::from_secs(60));
+//! loop {
+//!     for (i, sensor) in sensors.iter().enumerate() {
+//!         if let Ok(reading) = sensor.read() {
+//!             merge.update(i, reading, Instant::now());
+//!         }
+//!     }
+//!     if let Some(reading) = merge.median(Instant::now()) {
+//!         println!("{} ppm CO₂", reading.co2());
+//!     }
+//! }
+//! #
+//! # Ok(())
+//! # }
+//! # #[cfg(not(feature = "hid"))]
+//! # fn main() {}
+//! ```
+
+use crate::Reading;
+use std::time::{Duration, Instant};
+
+/// The latest readings of a fixed number of sensors.
+#[derive(Debug, Clone)]
+pub struct Merge {
+    latest: Vec<Option<(Instant, Reading)>>,
+    max_age: Duration,
+}
+
+impl Merge {
+    /// Creates an empty merge of `sources` sensors, ignoring the readings
+    /// older than `max_age`.
+    pub fn new(sources: usize, max_age: Duration) -> Self {
+        Self {
+            latest: vec![None; sources],
+            max_age,
+        }
+    }
+
+    /// Records a reading of the sensor with the index `source`, taken at
+    /// `at`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `source` is out of range.
+    pub fn update(&mut self, source: usize, reading: Reading, at: Instant) {
+        self.latest[source] = Some((at, reading));
+    }
+
+    /// Returns the number of sensors with a reading that is not older than
+    /// the maximum age at `now`.
+    pub fn fresh(&self, now: Instant) -> usize {
+        self.fresh_readings(now).count()
+    }
+
+    /// Returns the median of the readings that are not older than the
+    /// maximum age at `now`, or `None` if there are none.
+    ///
+    /// The temperature and CO₂ concentration are handled separately. With an
+    /// even number of readings, the two middle values are averaged.
+    pub fn median(&self, now: Instant) -> Option<Reading> {
+        let mut temperatures = self
+            .fresh_readings(now)
+            .map(|reading| reading.temperature)
+            .collect::<Vec<_>>();
+        let mut co2 = self
+            .fresh_readings(now)
+            .map(|reading| reading.co2)
+            .collect::<Vec<_>>();
+        if co2.is_empty() {
+            return None;
+        }
+
+        temperatures.sort_by(f32::total_cmp);
+        co2.sort_unstable();
+        let mid = co2.len() / 2;
+        let reading = if co2.len() % 2 == 1 {
+            Reading {
+                temperature: temperatures[mid],
+                co2: co2[mid],
+            }
+        } else {
+            Reading {
+                temperature: (temperatures[mid - 1] + temperatures[mid]) / 2.0,
+                co2: ((u32::from(co2[mid - 1]) + u32::from(co2[mid])) / 2) as u16,
+            }
+        };
+        Some(reading)
+    }
+
+    fn fresh_readings(&self, now: Instant) -> impl Iterator<Item = &Reading> {
+        self.latest
+            .iter()
+            .flatten()
+            .filter_map(move |(at, reading)| {
+                (now.saturating_duration_since(*at) <= self.max_age).then_some(reading)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Merge;
+    use crate::Reading;
+    use std::time::{Duration, Instant};
+
+    fn reading(temperature: f32, co2: u16) -> Reading {
+        Reading { temperature, co2 }
+    }
+
+    #[test]
+    fn test_median() {
+        let start = Instant::now();
+        let mut merge = Merge::new(3, Duration::from_secs(60));
+        assert_eq!(merge.median(start), None);
+
+        merge.update(0, reading(21.0, 800), start);
+        merge.update(1, reading(22.0, 5000), start);
+        assert_eq!(merge.median(start), Some(reading(21.5, 2900)));
+
+        // the outlier doesn't matter
+        merge.update(2, reading(20.0, 820), start);
+        assert_eq!(merge.median(start), Some(reading(21.0, 820)));
+        assert_eq!(merge.fresh(start), 3);
+    }
+
+    #[test]
+    fn test_stale() {
+        let start = Instant::now();
+        let mut merge = Merge::new(2, Duration::from_secs(60));
+        merge.update(0, reading(21.0, 800), start);
+        merge.update(1, reading(22.0, 900), start + Duration::from_secs(30));
+
+        let now = start + Duration::from_secs(61);
+        assert_eq!(merge.fresh(now), 1);
+        assert_eq!(merge.median(now), Some(reading(22.0, 900)));
+        assert_eq!(merge.median(now + Duration::from_secs(30)), None);
+    }
+}
@@ -0,0 +1,237 @@
+//! A scriptable [`Backend`] for testing code built on top of [`Sensor`].
+//!
+//! [`MockBackend`] replays a fixed list of reports in a loop, and can be
+//! configured to inject faults, like a bad checksum on every 10th read or a
+//! disconnection after a while.
+//!
+//! [`Sensor`]: crate::Sensor
+//!
+//! # Example
+//!
+//! ```
+//! # use co2mon::mock::{Fault, MockBackend};
+//! # use co2mon::{Error, OpenOptions, Result};
+//! # fn main() -> Result<()> {
+//! #
+//! let co2 = [0x50, 0x04, 0x57, 0xab, 0x0d, 0x00, 0x00, 0x00];
+//! let backend = MockBackend::new(vec![co2])
+//!     .fail_every(10, Fault::Checksum)
+//!     .disconnect_after_reads(25);
+//! let sensor = OpenOptions::new().open_backend(backend)?;
+//! for i in 1..=25 {
+//!     let reading = sensor.read_one();
+//!     assert_eq!(reading.is_err(), i % 10 == 0);
+//! }
+//! assert!(matches!(sensor.read_one(), Err(Error::Disconnected)));
+//! #
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::clock::{Clock, SystemClock};
+use crate::{Backend, Error, Result};
+use std::fmt::{self, Debug, Formatter};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// A fault to inject in the stream of reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Fault {
+    /// The report has a bad checksum.
+    Checksum,
+    /// Only a part of the report is returned.
+    ShortRead,
+    /// The read times out without returning any data.
+    Timeout,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    reads: usize,
+    start: Option<Instant>,
+}
+
+/// A fake device replaying a list of reports, with optional faults.
+///
+/// The reports are returned as they are, so they should normally be
+/// unencrypted. Feature reports are accepted and ignored.
+pub struct MockBackend {
+    reports: Vec<[u8; 8]>,
+    faults: Vec<(usize, Fault)>,
+    disconnect_after_reads: Option<usize>,
+    disconnect_after: Option<Duration>,
+    clock: Box<dyn Clock>,
+    state: Mutex<State>,
+}
+
+impl MockBackend {
+    /// Creates a backend returning `reports` in a loop.
+    ///
+    /// When `reports` is empty, every read times out.
+    pub fn new(reports: Vec<[u8; 8]>) -> Self {
+        Self {
+            reports,
+            faults: Vec::new(),
+            disconnect_after_reads: None,
+            disconnect_after: None,
+            clock: Box::new(SystemClock),
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn with_clock(mut self, clock: Box<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Injects `fault` on every `n`th read.
+    ///
+    /// When multiple faults apply to the same read, the one that was added
+    /// first wins.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is `0`.
+    pub fn fail_every(mut self, n: usize, fault: Fault) -> Self {
+        assert!(n > 0, "the fault period must be positive");
+        self.faults.push((n, fault));
+        self
+    }
+
+    /// Makes every read after the first `reads` fail with
+    /// [`Error::Disconnected`], as if the device was unplugged.
+    pub fn disconnect_after_reads(mut self, reads: usize) -> Self {
+        self.disconnect_after_reads = Some(reads);
+        self
+    }
+
+    /// Makes every read fail with [`Error::Disconnected`] once `duration` has
+    /// passed since the first read, as if the device was unplugged.
+    pub fn disconnect_after(mut self, duration: Duration) -> Self {
+        self.disconnect_after = Some(duration);
+        self
+    }
+
+    /// Returns the number of reads attempted so far.
+    pub fn reads(&self) -> usize {
+        self.state.lock().unwrap().reads
+    }
+}
+
+impl Debug for MockBackend {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("MockBackend")
+            .field("reports", &self.reports)
+            .field("faults", &self.faults)
+            .field("disconnect_after_reads", &self.disconnect_after_reads)
+            .field("disconnect_after", &self.disconnect_after)
+            .field("state", &self.state)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Backend for MockBackend {
+    fn send_feature_report(&self, _data: &[u8]) -> Result<()> {
+        Ok(())
+    }
+
+    // `usize::is_multiple_of` needs Rust 1.87
+    #[allow(clippy::manual_is_multiple_of)]
+    fn read_timeout(&self, buf: &mut [u8], _timeout: i32) -> Result<usize> {
+        let mut state = self.state.lock().unwrap();
+        let now = self.clock.now();
+        let start = *state.start.get_or_insert(now);
+        let index = state.reads;
+        state.reads += 1;
+
+        let disconnected = self
+            .disconnect_after_reads
+            .is_some_and(|reads| index >= reads)
+            || self
+                .disconnect_after
+                .is_some_and(|duration| now.duration_since(start) >= duration);
+        if disconnected {
+            return Err(Error::Disconnected);
+        }
+
+        if self.reports.is_empty() {
+            return Ok(0);
+        }
+        let mut report = self.reports[index % self.reports.len()];
+        let fault = self
+            .faults
+            .iter()
+            .find(|&&(n, _)| (index + 1) % n == 0)
+            .map(|&(_, fault)| fault);
+        let len = match fault {
+            Some(Fault::Checksum) => {
+                report[3] = !report[3];
+                report.len()
+            }
+            Some(Fault::ShortRead) => report.len() / 2,
+            Some(Fault::Timeout) => 0,
+            None => report.len(),
+        };
+
+        let len = len.min(buf.len());
+        buf[..len].copy_from_slice(&report[..len]);
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Fault, MockBackend};
+    use crate::fixtures::{fake_clock, CO2, TEMPERATURE};
+    use crate::{Backend, Error, OpenOptions, SingleReading};
+    use std::time::Duration;
+
+    #[test]
+    fn test_faults() {
+        let backend = MockBackend::new(vec![CO2, TEMPERATURE])
+            .fail_every(3, Fault::Checksum)
+            .fail_every(2, Fault::ShortRead)
+            .fail_every(5, Fault::Timeout);
+        let sensor = OpenOptions::new().open_backend(backend).unwrap();
+
+        let results = (0..6).map(|_| sensor.read_one()).collect::<Vec<_>>();
+        assert!(matches!(results[0], Ok(SingleReading::CO2(_))));
+        assert!(matches!(results[1], Err(Error::InvalidMessage)));
+        assert!(matches!(results[2], Err(Error::Checksum)));
+        assert!(matches!(results[3], Err(Error::InvalidMessage)));
+        assert!(matches!(results[4], Err(Error::Timeout)));
+        assert!(matches!(results[5], Err(Error::Checksum)));
+    }
+
+    #[test]
+    fn test_disconnect() {
+        let backend = MockBackend::new(vec![CO2]).disconnect_after_reads(2);
+        let mut buf = [0; 8];
+        assert_eq!(backend.read_timeout(&mut buf, -1).unwrap(), 8);
+        assert_eq!(backend.read_timeout(&mut buf, -1).unwrap(), 8);
+        assert!(matches!(
+            backend.read_timeout(&mut buf, -1),
+            Err(Error::Disconnected)
+        ));
+        assert_eq!(backend.reads(), 3);
+
+        let backend = MockBackend::new(vec![CO2]).disconnect_after(Duration::ZERO);
+        assert!(backend.read_timeout(&mut buf, -1).is_err());
+
+        let backend = MockBackend::new(vec![CO2])
+            .disconnect_after(Duration::from_secs(3))
+            .with_clock(fake_clock(Duration::from_secs(1)));
+        for _ in 0..3 {
+            assert_eq!(backend.read_timeout(&mut buf, -1).unwrap(), 8);
+        }
+        assert!(matches!(
+            backend.read_timeout(&mut buf, -1),
+            Err(Error::Disconnected)
+        ));
+
+        let backend = MockBackend::new(Vec::new());
+        assert_eq!(backend.read_timeout(&mut buf, -1).unwrap(), 0);
+    }
+}
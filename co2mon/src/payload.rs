@@ -0,0 +1,145 @@
+//! Compact binary encodings of a [`Reading`], suitable for LoRaWAN uplinks.
+//!
+//! The compact format takes 4 bytes: the CO₂ concentration in ppm as a
+//! big-endian `u16`, followed by the temperature in 0.01 °C as a big-endian
+//! `i16`.
+//!
+//! Alternatively, [`encode_cayenne_lpp`] produces a [Cayenne LPP][lpp] payload
+//! which most network servers can decode without a custom payload formatter.
+//! It contains a temperature (type `0x67`, 0.1 °C resolution) on channel 1
+//! and a concentration (extended type `0x7d`, in ppm) on channel 2.
+//!
+//! [lpp]: https://developers.mydevices.com/cayenne/docs/lora/#lora-cayenne-low-power-payload
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use co2mon::{payload, Result, Sensor};
+//! # #[cfg(feature = "hid")]
+//! # fn main() -> Result<()> {
+//! #
+//! let sensor = Sensor::open_default()?;
+//! let reading = sensor.read()?;
+//! let uplink = payload::encode(&reading);
+//! assert_eq!(payload::decode(&uplink)?.co2(), reading.co2());
+//! #
+//! # Ok(())
+//! # }
+//! # #[cfg(not(feature = "hid"))]
+//! # fn main() {}
+//! ```
+
+use crate::{Error, Reading, Result};
+
+const LPP_TEMPERATURE: u8 = 0x67;
+const LPP_CONCENTRATION: u8 = 0x7d;
+
+/// Encodes a reading in the compact format.
+pub fn encode(reading: &Reading) -> [u8; 4] {
+    let co2 = reading.co2.to_be_bytes();
+    let temperature = ((reading.temperature * 100.0).round() as i16).to_be_bytes();
+    [co2[0], co2[1], temperature[0], temperature[1]]
+}
+
+/// Decodes a reading in the compact format.
+///
+/// # Errors
+///
+/// [`Error::InvalidMessage`] will be returned if the payload does not have
+/// the expected length.
+pub fn decode(payload: &[u8]) -> Result<Reading> {
+    match *payload {
+        [co2_hi, co2_lo, temperature_hi, temperature_lo] => Ok(Reading {
+            temperature: f32::from(i16::from_be_bytes([temperature_hi, temperature_lo])) * 0.01,
+            co2: u16::from_be_bytes([co2_hi, co2_lo]),
+        }),
+        _ => Err(Error::InvalidMessage),
+    }
+}
+
+/// Encodes a reading as a Cayenne LPP payload.
+pub fn encode_cayenne_lpp(reading: &Reading) -> [u8; 8] {
+    let temperature = ((reading.temperature * 10.0).round() as i16).to_be_bytes();
+    let co2 = reading.co2.to_be_bytes();
+    [
+        1,
+        LPP_TEMPERATURE,
+        temperature[0],
+        temperature[1],
+        2,
+        LPP_CONCENTRATION,
+        co2[0],
+        co2[1],
+    ]
+}
+
+/// Decodes a Cayenne LPP payload produced by [`encode_cayenne_lpp`].
+///
+/// The channels may appear in any order, but both of them must be present.
+///
+/// # Errors
+///
+/// [`Error::InvalidMessage`] will be returned if the payload is malformed or
+/// incomplete.
+pub fn decode_cayenne_lpp(payload: &[u8]) -> Result<Reading> {
+    let mut temperature = None;
+    let mut co2 = None;
+    for field in payload.chunks(4) {
+        match *field {
+            [_, LPP_TEMPERATURE, hi, lo] => {
+                temperature = Some(f32::from(i16::from_be_bytes([hi, lo])) * 0.1)
+            }
+            [_, LPP_CONCENTRATION, hi, lo] => co2 = Some(u16::from_be_bytes([hi, lo])),
+            _ => return Err(Error::InvalidMessage),
+        }
+    }
+    match (temperature, co2) {
+        (Some(temperature), Some(co2)) => Ok(Reading { temperature, co2 }),
+        _ => Err(Error::InvalidMessage),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Reading;
+
+    #[test]
+    fn test_compact() {
+        let reading = Reading {
+            temperature: 21.4375,
+            co2: 1111,
+        };
+        let payload = super::encode(&reading);
+        assert_eq!(payload, [0x04, 0x57, 0x08, 0x60]);
+
+        let decoded = super::decode(&payload).unwrap();
+        assert_eq!(decoded.co2(), 1111);
+        assert!((decoded.temperature() - 21.44).abs() < 1e-4);
+
+        let negative = Reading {
+            temperature: -5.0,
+            co2: 400,
+        };
+        let decoded = super::decode(&super::encode(&negative)).unwrap();
+        assert!((decoded.temperature() + 5.0).abs() < 1e-4);
+
+        assert!(super::decode(&payload[..3]).is_err());
+    }
+
+    #[test]
+    fn test_cayenne_lpp() {
+        let reading = Reading {
+            temperature: 21.4375,
+            co2: 1111,
+        };
+        let payload = super::encode_cayenne_lpp(&reading);
+        assert_eq!(payload, [0x01, 0x67, 0x00, 0xd6, 0x02, 0x7d, 0x04, 0x57]);
+
+        let decoded = super::decode_cayenne_lpp(&payload).unwrap();
+        assert_eq!(decoded.co2(), 1111);
+        assert!((decoded.temperature() - 21.4).abs() < 1e-4);
+
+        assert!(super::decode_cayenne_lpp(&payload[..4]).is_err());
+        assert!(super::decode_cayenne_lpp(&payload[..6]).is_err());
+    }
+}
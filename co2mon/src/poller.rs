@@ -0,0 +1,524 @@
+use crate::{Error, Reading, Sensor, Timestamped};
+#[cfg(unix)]
+use std::io::{ErrorKind, Read, Write};
+#[cfg(unix)]
+use std::os::unix::io::{AsRawFd, RawFd};
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+#[cfg(unix)]
+use std::sync::mpsc::{TryIter, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// How long to wait before reading again after an error.
+const ERROR_DELAY: Duration = Duration::from_secs(1);
+
+/// The longest a single read can take, which also bounds how long dropping
+/// the poller waits for the thread to stop.
+const MAX_READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How many readings a subscriber can fall behind before it's dropped.
+const SUBSCRIBER_CAPACITY: usize = 64;
+
+#[derive(Default)]
+struct Shared {
+    latest: Mutex<Option<Timestamped<Reading>>>,
+    error: Mutex<Option<Error>>,
+    subscribers: Mutex<Vec<Subscriber>>,
+    stop: AtomicBool,
+}
+
+struct Subscriber {
+    tx: SyncSender<Reading>,
+    #[cfg(unix)]
+    notify: Option<UnixStream>,
+}
+
+impl Subscriber {
+    /// Sends `reading`, returning `false` if the receiver is gone or has
+    /// fallen too far behind.
+    fn send(&self, reading: Reading) -> bool {
+        // a full channel is dropped instead of blocking the poller
+        if self.tx.try_send(reading).is_err() {
+            return false;
+        }
+        #[cfg(unix)]
+        if let Some(notify) = &self.notify {
+            // a full buffer means there is a notification pending already
+            match (&*notify).write(&[0]) {
+                Ok(_) => {}
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+                Err(_) => return false,
+            }
+        }
+        true
+    }
+}
+
+/// Reads a sensor continuously in a background thread.
+///
+/// The thread calls [`Sensor::read`] in a loop, and keeps the latest reading
+/// around. Failed reads are retried after a short delay, and the last error
+/// is available from [`Poller::take_error`]. To see every error, use
+/// [`Poller::with_error_hook`].
+///
+/// Dropping the poller stops the thread, after waiting for the current read
+/// to finish. For that reason, the reads time out after 10 seconds at most,
+/// even if the sensor has a longer timeout or none at all.
+///
+/// # Example
+///
+/// ```no_run
+/// # use co2mon::{Poller, Result, Sensor};
+/// # #[cfg(feature = "hid")]
+/// # fn main() -> Result<()> {
+/// #
+/// let poller = Poller::new(Sensor::open_default()?);
+/// for reading in poller.subscribe() {
+///     println!("{} ppm CO₂", reading.co2());
+/// }
+/// #
+/// # Ok(())
+/// # }
+/// # #[cfg(not(feature = "hid"))]
+/// # fn main() {}
+/// ```
+pub struct Poller {
+    shared: Arc<Shared>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Poller {
+    /// Starts reading `sensor` in a background thread.
+    pub fn new(sensor: Sensor) -> Self {
+        Self::with_error_hook(sensor, |_| {})
+    }
+
+    /// Starts reading `sensor` in a background thread, calling `on_error`
+    /// on that thread for each failed read.
+    ///
+    /// The errors are also kept for [`Poller::take_error`], as usual.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use co2mon::{Poller, Result, Sensor};
+    /// # #[cfg(feature = "hid")]
+    /// # fn main() -> Result<()> {
+    /// #
+    /// let poller = Poller::with_error_hook(Sensor::open_default()?, |e| {
+    ///     eprintln!("read failed: {}", e);
+    /// });
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "hid"))]
+    /// # fn main() {}
+    /// ```
+    pub fn with_error_hook<F>(sensor: Sensor, mut on_error: F) -> Self
+    where
+        F: FnMut(&Error) + Send + 'static,
+    {
+        let shared = Arc::new(Shared::default());
+        let thread = {
+            let shared = shared.clone();
+            thread::spawn(move || run(&sensor, &shared, &mut on_error))
+        };
+        Self {
+            shared,
+            thread: Some(thread),
+        }
+    }
+
+    /// Returns the latest reading, or `None` if there wasn't any yet.
+    ///
+    /// The reading is kept when the following reads fail, see
+    /// [`Poller::latest_timestamped`] to find out how old it is.
+    pub fn latest(&self) -> Option<Reading> {
+        self.latest_timestamped().map(Timestamped::into_value)
+    }
+
+    /// Returns the latest reading along with the time at which it was
+    /// received, or `None` if there wasn't any yet.
+    pub fn latest_timestamped(&self) -> Option<Timestamped<Reading>> {
+        self.shared.latest.lock().unwrap().clone()
+    }
+
+    /// Returns the error of the last read, if it failed, and clears it.
+    ///
+    /// The error is also cleared by the next successful read, so this
+    /// returns `None` while the sensor works.
+    pub fn take_error(&self) -> Option<Error> {
+        self.shared.error.lock().unwrap().take()
+    }
+
+    /// Returns a channel which receives every subsequent reading.
+    ///
+    /// The channel is closed when the poller is dropped. It holds up to 64
+    /// readings: a subscriber which falls further behind is dropped, and its
+    /// channel is closed after the readings it already holds, so that it
+    /// neither holds back the poller nor uses more and more memory.
+    pub fn subscribe(&self) -> Receiver<Reading> {
+        let (tx, rx) = mpsc::sync_channel(SUBSCRIBER_CAPACITY);
+        self.shared.subscribers.lock().unwrap().push(Subscriber {
+            tx,
+            #[cfg(unix)]
+            notify: None,
+        });
+        rx
+    }
+
+    /// Returns a channel which receives every subsequent reading, along with
+    /// a file descriptor that becomes readable when readings arrive.
+    ///
+    /// This allows waiting for readings with `poll`, `epoll` or an event loop,
+    /// together with other sources. See [`FdReceiver`] for an example, and
+    /// [`Poller::subscribe`] for what happens to a subscriber that falls
+    /// behind.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] if the socket pair can't be created.
+    #[cfg(unix)]
+    pub fn subscribe_fd(&self) -> crate::Result<FdReceiver> {
+        let (notify, stream) = UnixStream::pair()?;
+        notify.set_nonblocking(true)?;
+        stream.set_nonblocking(true)?;
+        let (tx, rx) = mpsc::sync_channel(SUBSCRIBER_CAPACITY);
+        self.shared.subscribers.lock().unwrap().push(Subscriber {
+            tx,
+            notify: Some(notify),
+        });
+        Ok(FdReceiver { rx, stream })
+    }
+}
+
+impl Drop for Poller {
+    fn drop(&mut self) {
+        self.shared.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// A channel of readings which can be waited for using its file descriptor.
+///
+/// The file descriptor becomes readable when a reading is sent, and stays so
+/// until [`FdReceiver::try_recv`] or [`FdReceiver::try_iter`] is called. Once
+/// the poller is dropped it is always readable, and `try_recv` returns
+/// [`TryRecvError::Disconnected`].
+///
+/// # Example
+///
+/// ```no_run
+/// # use co2mon::{Poller, Result, Sensor};
+/// # #[cfg(feature = "hid")]
+/// # fn main() -> Result<()> {
+/// #
+/// use std::os::unix::io::AsRawFd;
+///
+/// let poller = Poller::new(Sensor::open_default()?);
+/// let readings = poller.subscribe_fd()?;
+/// let fd = readings.as_raw_fd();
+/// // register `fd` with the event loop, and when it becomes readable:
+/// for reading in readings.try_iter() {
+///     println!("{} ppm CO₂", reading.co2());
+/// }
+/// #
+/// # Ok(())
+/// # }
+/// # #[cfg(not(feature = "hid"))]
+/// # fn main() {}
+/// ```
+#[cfg(unix)]
+#[derive(Debug)]
+pub struct FdReceiver {
+    rx: Receiver<Reading>,
+    stream: UnixStream,
+}
+
+#[cfg(unix)]
+impl FdReceiver {
+    /// Returns a reading if one is available, without blocking.
+    pub fn try_recv(&self) -> Result<Reading, TryRecvError> {
+        self.clear();
+        self.rx.try_recv()
+    }
+
+    /// Returns an iterator over the readings that are available, without
+    /// blocking.
+    pub fn try_iter(&self) -> TryIter<'_, Reading> {
+        self.clear();
+        self.rx.try_iter()
+    }
+
+    /// Consumes the pending notifications. They must be read before the
+    /// channel, so that a reading sent in between still wakes the caller.
+    fn clear(&self) {
+        let mut buf = [0; 64];
+        loop {
+            match (&self.stream).read(&mut buf) {
+                Ok(0) => break,
+                Ok(_) => {}
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(_) => break,
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+impl AsRawFd for FdReceiver {
+    fn as_raw_fd(&self) -> RawFd {
+        self.stream.as_raw_fd()
+    }
+}
+
+fn run(sensor: &Sensor, shared: &Shared, on_error: &mut dyn FnMut(&Error)) {
+    let timeout = sensor
+        .timeout()
+        .map_or(MAX_READ_TIMEOUT, |timeout| timeout.min(MAX_READ_TIMEOUT));
+    while !shared.stop.load(Ordering::Relaxed) {
+        match sensor.read_timeout(timeout) {
+            Ok(reading) => {
+                *shared.error.lock().unwrap() = None;
+                *shared.latest.lock().unwrap() = Some(sensor.timestamp(reading.clone()));
+                shared
+                    .subscribers
+                    .lock()
+                    .unwrap()
+                    .retain(|subscriber| subscriber.send(reading.clone()));
+            }
+            Err(e) => {
+                on_error(&e);
+                *shared.error.lock().unwrap() = Some(e);
+                thread::sleep(ERROR_DELAY);
+            }
+        }
+    }
+    // close the channels, even if some receivers outlive the poller
+    shared.subscribers.lock().unwrap().clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Poller, SUBSCRIBER_CAPACITY};
+    use crate::fixtures::{CO2, TEMPERATURE};
+    use crate::mock::MockBackend;
+    use crate::{Backend, Error, OpenOptions, Result};
+    use std::sync::mpsc::{self, Receiver, SyncSender, TryRecvError};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    /// A device which never sends anything, recording the read timeouts.
+    struct SilentDevice {
+        timeouts: Arc<Mutex<Vec<i32>>>,
+    }
+
+    impl Backend for SilentDevice {
+        fn send_feature_report(&self, _data: &[u8]) -> Result<()> {
+            Ok(())
+        }
+
+        fn read_timeout(&self, _buf: &mut [u8], timeout: i32) -> Result<usize> {
+            self.timeouts.lock().unwrap().push(timeout);
+            // wait a bit, but not for the whole timeout
+            thread::sleep(Duration::from_millis(timeout.clamp(0, 50) as u64));
+            Ok(0)
+        }
+    }
+
+    /// A device which sends a report each time it is given a permit.
+    struct GatedDevice {
+        backend: MockBackend,
+        permits: Mutex<Receiver<()>>,
+    }
+
+    impl GatedDevice {
+        fn new(reports: Vec<[u8; 8]>) -> (SyncSender<()>, Self) {
+            // a rendezvous channel, so that sending a permit waits for a read
+            let (tx, rx) = mpsc::sync_channel(0);
+            let device = Self {
+                backend: MockBackend::new(reports),
+                permits: Mutex::new(rx),
+            };
+            (tx, device)
+        }
+    }
+
+    impl Backend for GatedDevice {
+        fn send_feature_report(&self, data: &[u8]) -> Result<()> {
+            self.backend.send_feature_report(data)
+        }
+
+        fn read_timeout(&self, buf: &mut [u8], timeout: i32) -> Result<usize> {
+            let timeout = Duration::from_millis(timeout.max(0) as u64);
+            match self.permits.lock().unwrap().recv_timeout(timeout) {
+                Ok(()) => self.backend.read_timeout(buf, -1),
+                Err(_) => Ok(0),
+            }
+        }
+    }
+
+    /// Waits for `condition` to become true, for 5 seconds at most.
+    fn wait_for(mut condition: impl FnMut() -> bool) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !condition() {
+            assert!(Instant::now() < deadline, "timed out");
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn test_poller() {
+        let backend = MockBackend::new(vec![CO2, TEMPERATURE]);
+        let sensor = OpenOptions::new().open_backend(backend).unwrap();
+        let poller = Poller::new(sensor);
+
+        let readings = poller.subscribe();
+        let reading = readings.recv().unwrap();
+        assert_eq!(reading.co2(), 1111);
+        assert_eq!(poller.latest().unwrap().co2(), 1111);
+
+        // the channel is closed, so this doesn't block forever
+        drop(poller);
+        assert!(readings.iter().all(|reading| reading.co2() == 1111));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_subscribe_fd() {
+        use std::io::Read;
+
+        let backend = MockBackend::new(vec![CO2, TEMPERATURE]);
+        let sensor = OpenOptions::new().open_backend(backend).unwrap();
+        let poller = Poller::new(sensor);
+
+        let readings = poller.subscribe_fd().unwrap();
+        // the descriptor becomes readable once there is a reading
+        wait_for(|| (&readings.stream).read(&mut [0]).is_ok());
+        assert_eq!(readings.try_recv().unwrap().co2(), 1111);
+        assert!(readings.try_iter().all(|reading| reading.co2() == 1111));
+
+        // the closed channel reads as end of file
+        drop(poller);
+        assert!(readings.try_iter().all(|reading| reading.co2() == 1111));
+        assert_eq!((&readings.stream).read(&mut [0]).unwrap(), 0);
+        assert_eq!(readings.try_recv(), Err(TryRecvError::Disconnected));
+    }
+
+    #[test]
+    fn test_poller_error() {
+        let backend = MockBackend::new(vec![CO2, TEMPERATURE]).disconnect_after_reads(2);
+        let sensor = OpenOptions::new().open_backend(backend).unwrap();
+        let poller = Poller::new(sensor);
+
+        let mut error = None;
+        wait_for(|| {
+            error = poller.take_error();
+            error.is_some()
+        });
+        assert!(matches!(error, Some(Error::Disconnected)));
+        // the last reading is kept, along with its age
+        let latest = poller.latest_timestamped().unwrap();
+        assert_eq!(latest.value().co2(), 1111);
+        assert!(latest.instant() <= Instant::now());
+    }
+
+    #[test]
+    fn test_error_hook() {
+        let backend = MockBackend::new(vec![CO2, TEMPERATURE]).disconnect_after_reads(2);
+        let sensor = OpenOptions::new().open_backend(backend).unwrap();
+        let (tx, errors) = mpsc::channel();
+        let poller = Poller::with_error_hook(sensor, move |e| {
+            let _ = tx.send(matches!(e, Error::Disconnected));
+        });
+
+        // every failed read is reported, not just the last one
+        let timeout = Duration::from_secs(5);
+        assert!(errors.recv_timeout(timeout).unwrap());
+        assert!(errors.recv_timeout(timeout).unwrap());
+        assert!(matches!(poller.take_error(), Some(Error::Disconnected)));
+
+        // the hook is dropped along with the thread
+        drop(poller);
+        assert!(errors.iter().all(|disconnected| disconnected));
+    }
+
+    #[test]
+    fn test_drop_while_reading() {
+        let timeouts = Arc::<Mutex<Vec<i32>>>::default();
+        let device = SilentDevice {
+            timeouts: timeouts.clone(),
+        };
+        // without a timeout, the read would block forever
+        let sensor = OpenOptions::new()
+            .timeout(None)
+            .open_backend(device)
+            .unwrap();
+        let poller = Poller::new(sensor);
+        wait_for(|| !timeouts.lock().unwrap().is_empty());
+
+        let start = Instant::now();
+        drop(poller);
+        assert!(start.elapsed() < Duration::from_secs(5));
+        let timeouts = timeouts.lock().unwrap();
+        assert!(timeouts
+            .iter()
+            .all(|&timeout| (1..=10_000).contains(&timeout)));
+    }
+
+    #[test]
+    fn test_unsubscribe() {
+        let (permits, device) = GatedDevice::new(vec![CO2, TEMPERATURE]);
+        let sensor = OpenOptions::new().open_backend(device).unwrap();
+        let poller = Poller::new(sensor);
+
+        let first = poller.subscribe();
+        let second = poller.subscribe();
+        while first.try_recv().is_err() {
+            permits.send(()).unwrap();
+        }
+        drop(first);
+        // the closed channel is removed on the next reading
+        while poller.shared.subscribers.lock().unwrap().len() != 1 {
+            permits.send(()).unwrap();
+        }
+        let readings = second.try_iter().collect::<Vec<_>>();
+        assert!(readings.len() >= 2);
+        assert!(readings.iter().all(|reading| reading.co2() == 1111));
+        drop(permits);
+    }
+
+    #[test]
+    fn test_slow_subscriber() {
+        let (permits, device) = GatedDevice::new(vec![CO2, TEMPERATURE]);
+        let sensor = OpenOptions::new().open_backend(device).unwrap();
+        let poller = Poller::new(sensor);
+
+        // a subscriber that never reads doesn't hold the poller back
+        let idle = poller.subscribe();
+        let readings = poller.subscribe();
+        let mut received = 0;
+        while received < SUBSCRIBER_CAPACITY + 10 {
+            permits.send(()).unwrap();
+            received += readings.try_iter().count();
+        }
+        // it was dropped once its channel was full, keeping what it received
+        assert_eq!(poller.shared.subscribers.lock().unwrap().len(), 1);
+        assert_eq!(idle.try_iter().count(), SUBSCRIBER_CAPACITY);
+        assert_eq!(idle.try_recv(), Err(TryRecvError::Disconnected));
+        // while the other subscriber still gets the readings
+        permits.send(()).unwrap();
+        permits.send(()).unwrap();
+        permits.send(()).unwrap();
+        wait_for(|| readings.try_recv().is_ok());
+        // let the thread stop without waiting for the read to time out
+        drop(permits);
+    }
+}
@@ -0,0 +1,71 @@
+use std::time::Duration;
+
+/// How [`Sensor`][crate::Sensor] reopens a device that stopped responding.
+///
+/// After a failed attempt, the delay before the next one is doubled, up to
+/// the maximum delay.
+///
+/// See [`OpenOptions::auto_reconnect`][crate::OpenOptions::auto_reconnect].
+///
+/// # Example
+///
+/// ```no_run
+/// # use co2mon::{OpenOptions, ReconnectPolicy, Result};
+/// # use std::time::Duration;
+/// # fn main() -> Result<()> {
+/// #
+/// let policy = ReconnectPolicy::new()
+///     .initial_delay(Duration::from_millis(500))
+///     .max_attempts(Some(10));
+/// let sensor = OpenOptions::new().auto_reconnect(policy).open()?;
+/// #
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    pub(crate) initial_delay: Duration,
+    pub(crate) max_delay: Duration,
+    pub(crate) max_attempts: Option<u32>,
+}
+
+impl ReconnectPolicy {
+    /// Creates a policy which waits for 1 second before the first attempt and
+    /// for at most 60 seconds between attempts, without giving up.
+    pub fn new() -> Self {
+        Self {
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(60),
+            max_attempts: None,
+        }
+    }
+
+    /// Sets the delay before the first attempt.
+    ///
+    /// A zero delay would make the attempts never wait, so it is rejected
+    /// with [`Error::InvalidTimeout`][crate::Error::InvalidTimeout] when
+    /// opening the sensor.
+    pub fn initial_delay(mut self, initial_delay: Duration) -> Self {
+        self.initial_delay = initial_delay;
+        self
+    }
+
+    /// Sets the longest delay between two attempts.
+    pub fn max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Sets the number of attempts after which the read fails, or `None` to
+    /// keep trying.
+    pub fn max_attempts(mut self, max_attempts: Option<u32>) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,163 @@
+use crate::{Reading, Result, Sensor, SingleReading, Stats};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// A sensor which can be used from multiple threads at once.
+///
+/// [`Sensor`] is [`Send`] but not [`Sync`], because the reports have to be
+/// read one at a time. This wraps it in a mutex, so it can be shared using
+/// an [`Arc`][std::sync::Arc], e.g. by the handlers of a web server.
+/// Concurrent reads wait for each other.
+///
+/// To read the sensor continuously instead of on demand, see
+/// [`Poller`][crate::Poller].
+///
+/// # Example
+///
+/// ```no_run
+/// # use co2mon::{Result, Sensor, SharedSensor};
+/// # use std::sync::Arc;
+/// # use std::thread;
+/// # #[cfg(feature = "hid")]
+/// # fn main() -> Result<()> {
+/// #
+/// let sensor = Arc::new(SharedSensor::new(Sensor::open_default()?));
+/// let handle = {
+///     let sensor = sensor.clone();
+///     thread::spawn(move || sensor.read())
+/// };
+/// println!("{:?} {:?}", sensor.read()?, handle.join().unwrap()?);
+/// #
+/// # Ok(())
+/// # }
+/// # #[cfg(not(feature = "hid"))]
+/// # fn main() {}
+/// ```
+pub struct SharedSensor {
+    sensor: Mutex<Sensor>,
+}
+
+impl SharedSensor {
+    /// Wraps a sensor.
+    pub fn new(sensor: Sensor) -> Self {
+        Self {
+            sensor: Mutex::new(sensor),
+        }
+    }
+
+    /// Locks the sensor, e.g. to take multiple readings or change its
+    /// settings without the other threads interfering.
+    pub fn lock(&self) -> MutexGuard<'_, Sensor> {
+        // a panic in another thread doesn't leave the sensor in a bad state
+        self.sensor.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns the wrapped sensor.
+    pub fn into_inner(self) -> Sensor {
+        self.sensor
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Takes a single reading, see [`Sensor::read_one`].
+    pub fn read_one(&self) -> Result<SingleReading> {
+        self.lock().read_one()
+    }
+
+    /// Takes a reading, see [`Sensor::read`].
+    pub fn read(&self) -> Result<Reading> {
+        self.lock().read()
+    }
+
+    /// Returns the read counters, see [`Sensor::stats`].
+    pub fn stats(&self) -> Stats {
+        self.lock().stats()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SharedSensor;
+    use crate::fixtures::{CO2, TEMPERATURE};
+    use crate::mock::MockBackend;
+    use crate::OpenOptions;
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_shared() {
+        let backend = MockBackend::new(vec![CO2, TEMPERATURE]);
+        let sensor = Arc::new(SharedSensor::new(
+            OpenOptions::new().open_backend(backend).unwrap(),
+        ));
+        let threads = (0..4)
+            .map(|_| {
+                let sensor = sensor.clone();
+                thread::spawn(move || sensor.read().unwrap().co2())
+            })
+            .collect::<Vec<_>>();
+        for thread in threads {
+            assert_eq!(thread.join().unwrap(), 1111);
+        }
+        assert!(sensor.stats().frames() >= 8);
+    }
+
+    #[test]
+    fn test_contention() {
+        let backend = MockBackend::new(vec![CO2, TEMPERATURE]);
+        let sensor = Arc::new(SharedSensor::new(
+            OpenOptions::new().open_backend(backend).unwrap(),
+        ));
+        let threads = (0..8)
+            .map(|_| {
+                let sensor = sensor.clone();
+                thread::spawn(move || {
+                    for _ in 0..50 {
+                        sensor.read().unwrap();
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        // the reads don't interleave, so each one takes exactly two reports
+        assert_eq!(sensor.stats().frames(), 8 * 50 * 2);
+    }
+
+    #[test]
+    fn test_lock() {
+        let backend = MockBackend::new(vec![CO2, TEMPERATURE]);
+        let sensor = Arc::new(SharedSensor::new(
+            OpenOptions::new().open_backend(backend).unwrap(),
+        ));
+        let guard = sensor.lock();
+        let thread = {
+            let sensor = sensor.clone();
+            thread::spawn(move || sensor.read().unwrap())
+        };
+        thread::sleep(Duration::from_millis(50));
+        assert!(!thread.is_finished());
+        assert_eq!(guard.stats().frames(), 0);
+        drop(guard);
+        assert_eq!(thread.join().unwrap().co2(), 1111);
+    }
+
+    #[test]
+    fn test_poisoned() {
+        let backend = MockBackend::new(vec![CO2, TEMPERATURE]);
+        let sensor = Arc::new(SharedSensor::new(
+            OpenOptions::new().open_backend(backend).unwrap(),
+        ));
+        let result = {
+            let sensor = sensor.clone();
+            thread::spawn(move || {
+                let _guard = sensor.lock();
+                panic!("the sensor is still usable");
+            })
+            .join()
+        };
+        assert!(result.is_err());
+        assert_eq!(sensor.read().unwrap().co2(), 1111);
+    }
+}
@@ -0,0 +1,90 @@
+use std::time::Duration;
+
+/// Counters describing the health of a [`Sensor`][crate::Sensor].
+///
+/// The counters start at zero when the sensor is opened and are updated by
+/// every read.
+///
+/// # Example
+///
+/// ```no_run
+/// # use co2mon::{Result, Sensor};
+/// # #[cfg(feature = "hid")]
+/// # fn main() -> Result<()> {
+/// #
+/// let sensor = Sensor::open_default()?;
+/// let _ = sensor.read();
+/// let stats = sensor.stats();
+/// println!("{} frames, {} bad checksums", stats.frames(), stats.checksum_errors());
+/// #
+/// # Ok(())
+/// # }
+/// # #[cfg(not(feature = "hid"))]
+/// # fn main() {}
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub(crate) frames: u64,
+    pub(crate) checksum_errors: u64,
+    pub(crate) invalid_messages: u64,
+    pub(crate) timeouts: u64,
+    pub(crate) reconnects: u64,
+    pub(crate) latencies: [u64; Stats::LATENCY_BUCKETS.len() + 1],
+}
+
+impl Stats {
+    /// The upper bounds of the buckets counted by [`Stats::latencies`].
+    pub const LATENCY_BUCKETS: [Duration; 5] = [
+        Duration::from_millis(10),
+        Duration::from_millis(100),
+        Duration::from_millis(500),
+        Duration::from_secs(1),
+        Duration::from_secs(5),
+    ];
+
+    /// Returns the number of complete reports received, including the ones
+    /// that could not be decoded.
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    /// Returns the number of reports with a bad checksum.
+    pub fn checksum_errors(&self) -> u64 {
+        self.checksum_errors
+    }
+
+    /// Returns the number of reports that were truncated or malformed.
+    pub fn invalid_messages(&self) -> u64 {
+        self.invalid_messages
+    }
+
+    /// Returns the number of single reads that timed out without receiving
+    /// anything.
+    pub fn timeouts(&self) -> u64 {
+        self.timeouts
+    }
+
+    /// Returns the number of times the device was reopened.
+    pub fn reconnects(&self) -> u64 {
+        self.reconnects
+    }
+
+    /// Returns a histogram of how long the reads took to receive a complete
+    /// report.
+    ///
+    /// Each element counts the reports received within the matching bound
+    /// of [`Stats::LATENCY_BUCKETS`], but not within the previous one, and
+    /// the last element counts the slower ones. The counts add up to
+    /// [`Stats::frames`].
+    pub fn latencies(&self) -> [u64; Stats::LATENCY_BUCKETS.len() + 1] {
+        self.latencies
+    }
+
+    pub(crate) fn record_latency(&mut self, latency: Duration) {
+        let bucket = Self::LATENCY_BUCKETS
+            .iter()
+            .position(|&bound| latency <= bound)
+            .unwrap_or(Self::LATENCY_BUCKETS.len());
+        self.latencies[bucket] += 1;
+    }
+}
@@ -0,0 +1,33 @@
+use std::time::{Instant, SystemTime};
+
+/// A reading, along with the time at which it was received.
+///
+/// See [`Sensor::read_timestamped`][crate::Sensor::read_timestamped].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timestamped<T> {
+    pub(crate) value: T,
+    pub(crate) instant: Instant,
+    pub(crate) system_time: SystemTime,
+}
+
+impl<T> Timestamped<T> {
+    /// Returns the reading.
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    /// Returns the reading, discarding the timestamps.
+    pub fn into_value(self) -> T {
+        self.value
+    }
+
+    /// Returns the monotonic time at which the report was received.
+    pub fn instant(&self) -> Instant {
+        self.instant
+    }
+
+    /// Returns the wall-clock time at which the report was received.
+    pub fn system_time(&self) -> SystemTime {
+        self.system_time
+    }
+}
@@ -0,0 +1,73 @@
+use co2mon::{Error, SingleReading};
+use std::ffi::OsStr;
+use std::fs;
+use std::path::Path;
+
+fn parse_hex(s: &str) -> u8 {
+    u8::from_str_radix(s, 16).unwrap_or_else(|_| panic!("invalid byte `{}`", s))
+}
+
+fn check(expected: &str, actual: co2mon::Result<SingleReading>) -> bool {
+    let (kind, value) = expected.split_once('=').unwrap_or((expected, ""));
+    match (kind, actual) {
+        ("co2", Ok(SingleReading::CO2(val))) => value.parse() == Ok(val),
+        ("temperature", Ok(SingleReading::Temperature(val))) => {
+            (value.parse::<f32>().unwrap() - val).abs() < 1e-3
+        }
+        ("humidity", Ok(SingleReading::Humidity(val))) => {
+            (value.parse::<f32>().unwrap() - val).abs() < 1e-3
+        }
+        ("unknown", Ok(SingleReading::Unknown(opcode, val))) => {
+            let (expected_opcode, expected_val) = value.split_once(',').unwrap();
+            parse_hex(expected_opcode) == opcode && expected_val.parse() == Ok(val)
+        }
+        ("invalid", Err(Error::InvalidMessage)) => true,
+        ("checksum", Err(Error::Checksum)) => true,
+        _ => false,
+    }
+}
+
+#[test]
+fn test_captures() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/captures");
+    let mut count = 0;
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension() != Some(OsStr::new("txt")) {
+            continue;
+        }
+
+        let mut key = [0; 8];
+        for (line_no, line) in fs::read_to_string(&path).unwrap().lines().enumerate() {
+            let line = line.trim();
+            if let Some(hex) = line.strip_prefix("# key:") {
+                let hex = hex.trim();
+                for (i, k) in key.iter_mut().enumerate() {
+                    *k = parse_hex(&hex[2 * i..2 * i + 2]);
+                }
+                continue;
+            }
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut fields = line.split_whitespace();
+            let mut report = [0; 8];
+            for b in report.iter_mut() {
+                *b = parse_hex(fields.next().unwrap());
+            }
+            let expected = fields.next().unwrap();
+            let actual = co2mon::decode_report(report, key);
+            assert!(
+                check(expected, actual),
+                "{}:{}: expected {}, got {:?}",
+                path.display(),
+                line_no + 1,
+                expected,
+                co2mon::decode_report(report, key)
+            );
+            count += 1;
+        }
+    }
+    assert!(count > 0);
+}
@@ -0,0 +1,17 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: [u8; 5]| {
+    if let Ok(reading) = zg_co2::decode(data) {
+        let _ = zg_co2::encode(&reading);
+    }
+
+    match zg_co2::decode_full(data) {
+        Ok(message) => assert_eq!(
+            zg_co2::decode(data).ok().as_ref(),
+            Some(message.reading()).filter(|_| message.checksum_valid())
+        ),
+        Err(_) => assert!(zg_co2::decode(data).is_err()),
+    }
+});
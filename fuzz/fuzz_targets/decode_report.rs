@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: ([u8; 8], [u8; 8])| {
+    let (report, key) = input;
+    let _ = co2mon::decode_report(report, key);
+});
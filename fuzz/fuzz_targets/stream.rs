@@ -0,0 +1,13 @@
+#![no_main]
+
+use co2mon::OpenOptions;
+use libfuzzer_sys::fuzz_target;
+use std::io::Cursor;
+
+fuzz_target!(|data: &[u8]| {
+    let sensor = OpenOptions::new()
+        .open_stream(Cursor::new(data.to_vec()))
+        .unwrap();
+    // reads until the end of the stream, which is reported as an error
+    while sensor.read_one().is_ok() {}
+});
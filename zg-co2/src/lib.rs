@@ -4,7 +4,7 @@
 
 //! A `no_std` crate implementing the [ZyAura ZG][ZG] CO₂ sensor protocol.
 //!
-//! This crate decodes the packets, but does not perform the decryption
+//! This crate decodes (and encodes) the packets, but does not perform the decryption
 //! commonly required for USB devices using this sensor. To read data from one
 //! of the compatible commercially-available USB sensors, use the
 //! [`co2mon`][co2mon] crate.
@@ -47,7 +47,8 @@ pub use error::Error;
 
 mod error;
 
-/// A specialized [`Result`][std::result::Result] type for the [`decode`] function.
+/// A specialized [`Result`][std::result::Result] type for the [`decode`] and
+/// [`decode_full`] functions.
 pub type Result<T> = result::Result<T, Error>;
 
 /// A single sensor reading.
@@ -79,6 +80,104 @@ pub enum SingleReading {
     Unknown(u8, u16),
 }
 
+/// Converts a raw temperature value, in 1/16 K, to °C.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(zg_co2::ticks_to_celsius(0), -273.15);
+/// ```
+pub fn ticks_to_celsius(ticks: u16) -> f32 {
+    f32::from(ticks) * 0.0625 - 273.15
+}
+
+/// Converts a temperature in °C to a raw value, in 1/16 K.
+///
+/// The result is rounded, and saturated if out of range.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(zg_co2::celsius_to_ticks(21.4125), 0x1269);
+/// ```
+pub fn celsius_to_ticks(celsius: f32) -> u16 {
+    // `f32::round` is not available in `core`, but the cast saturates
+    ((celsius + 273.15) * 16.0 + 0.5) as u16
+}
+
+/// Converts a raw relative humidity value, in 0.01 %, to %.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(zg_co2::ticks_to_humidity(4500), 45.0);
+/// ```
+pub fn ticks_to_humidity(ticks: u16) -> f32 {
+    f32::from(ticks) * 0.01
+}
+
+/// Converts a relative humidity in % to a raw value, in 0.01 %.
+///
+/// The result is rounded, and saturated if out of range.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(zg_co2::humidity_to_ticks(45.0), 4500);
+/// ```
+pub fn humidity_to_ticks(humidity: f32) -> u16 {
+    (humidity * 100.0 + 0.5) as u16
+}
+
+/// A decoded message, with both the raw and the converted value.
+///
+/// # Example
+///
+/// ```
+/// # use zg_co2::{Result, SingleReading};
+/// # fn main() -> Result<()> {
+/// #
+/// let message = zg_co2::decode_full([0x42, 0x12, 0x69, 0xbd, 0x0d])?;
+/// assert_eq!(message.opcode(), b'B');
+/// assert_eq!(message.raw_value(), 0x1269);
+/// assert!(message.checksum_valid());
+/// if let SingleReading::Temperature(val) = message.reading() {
+///     println!("{} °C", val);
+/// }
+/// #
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    opcode: u8,
+    raw_value: u16,
+    reading: SingleReading,
+    checksum_valid: bool,
+}
+
+impl Message {
+    /// Returns the opcode, i.e. the type of the reading.
+    pub fn opcode(&self) -> u8 {
+        self.opcode
+    }
+
+    /// Returns the value as sent by the sensor, before any conversion.
+    pub fn raw_value(&self) -> u16 {
+        self.raw_value
+    }
+
+    /// Returns the converted reading.
+    pub fn reading(&self) -> &SingleReading {
+        &self.reading
+    }
+
+    /// Returns whether the checksum of the message matched.
+    pub fn checksum_valid(&self) -> bool {
+        self.checksum_valid
+    }
+}
+
 /// Decodes a message from the sensor.
 ///
 /// # Example
@@ -91,22 +190,65 @@ pub enum SingleReading {
 ///
 /// An error will be returned if the message could not be decoded.
 pub fn decode(data: [u8; 5]) -> Result<SingleReading> {
+    let message = decode_full(data)?;
+    if !message.checksum_valid {
+        return Err(Error::Checksum);
+    }
+    Ok(message.reading)
+}
+
+/// Decodes a message from the sensor, keeping the raw value.
+///
+/// Unlike [`decode`], this doesn't fail on checksum errors, but reports them
+/// in [`Message::checksum_valid`].
+///
+/// # Errors
+///
+/// [`Error::InvalidMessage`] will be returned if the message does not finish
+/// with `0x0d`.
+pub fn decode_full(data: [u8; 5]) -> Result<Message> {
     if data[4] != 0x0d {
         return Err(Error::InvalidMessage);
     }
 
-    if data[0].wrapping_add(data[1]).wrapping_add(data[2]) != data[3] {
-        return Err(Error::Checksum);
-    }
+    let opcode = data[0];
+    let raw_value = u16::from(data[1]) << 8 | u16::from(data[2]);
+    let reading = match opcode {
+        b'A' => SingleReading::Humidity(ticks_to_humidity(raw_value)),
+        b'B' => SingleReading::Temperature(ticks_to_celsius(raw_value)),
+        b'P' => SingleReading::CO2(raw_value),
+        _ => SingleReading::Unknown(opcode, raw_value),
+    };
+    let checksum_valid = opcode.wrapping_add(data[1]).wrapping_add(data[2]) == data[3];
+    Ok(Message {
+        opcode,
+        raw_value,
+        reading,
+        checksum_valid,
+    })
+}
 
-    let value = u16::from(data[1]) << 8 | u16::from(data[2]);
-    let reading = match data[0] {
-        b'A' => SingleReading::Humidity(f32::from(value) * 0.01),
-        b'B' => SingleReading::Temperature(f32::from(value) * 0.0625 - 273.15),
-        b'P' => SingleReading::CO2(value),
-        _ => SingleReading::Unknown(data[0], value),
+/// Encodes a reading into a message, as sent by the sensor.
+///
+/// Values which are out of range for the protocol are saturated.
+///
+/// # Example
+///
+/// ```
+/// # use zg_co2::SingleReading;
+/// let message = zg_co2::encode(&SingleReading::CO2(1111));
+/// assert_eq!(message, [0x50, 0x04, 0x57, 0xab, 0x0d]);
+/// ```
+pub fn encode(reading: &SingleReading) -> [u8; 5] {
+    let (opcode, value) = match *reading {
+        SingleReading::Humidity(val) => (b'A', humidity_to_ticks(val)),
+        SingleReading::Temperature(val) => (b'B', celsius_to_ticks(val)),
+        SingleReading::CO2(val) => (b'P', val),
+        SingleReading::Unknown(opcode, val) => (opcode, val),
     };
-    Ok(reading)
+    let [hi, lo] = value.to_be_bytes();
+    let checksum = opcode.wrapping_add(hi).wrapping_add(lo);
+    [opcode, hi, lo, checksum, 0x0d]
 }
 
 #[cfg(test)]
@@ -114,6 +256,7 @@ mod tests {
     use super::{Error, SingleReading};
 
     #[test]
+    #[allow(clippy::assertions_on_constants)]
     fn test_decode() {
         match super::decode([0x50, 0x04, 0x57, 0xab, 0x0d]) {
             Ok(SingleReading::CO2(val)) => assert_eq!(val, 1111),
@@ -140,4 +283,119 @@ mod tests {
             _ => assert!(false),
         }
     }
+
+    #[test]
+    fn test_decode_full() {
+        let message = super::decode_full([0x42, 0x12, 0x69, 0xbd, 0x0d]).unwrap();
+        assert_eq!(message.opcode(), b'B');
+        assert_eq!(message.raw_value(), 4713);
+        assert_eq!(
+            *message.reading(),
+            SingleReading::Temperature(4713.0 * 0.0625 - 273.15)
+        );
+        assert!(message.checksum_valid());
+
+        let message = super::decode_full([0x50, 0x04, 0x57, 0x00, 0x0d]).unwrap();
+        assert_eq!(*message.reading(), SingleReading::CO2(1111));
+        assert!(!message.checksum_valid());
+
+        match super::decode_full([0x50, 0x04, 0x57, 0xab, 0x00]) {
+            Err(Error::InvalidMessage) => {}
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn test_encode() {
+        let messages = [
+            [0x50, 0x04, 0x57, 0xab, 0x0d],
+            [0x41, 0x00, 0x00, 0x41, 0x0d],
+            [0x42, 0x12, 0x69, 0xbd, 0x0d],
+            [0x6d, 0x12, 0x34, 0xb3, 0x0d],
+        ];
+        for message in messages {
+            let reading = super::decode(message).unwrap();
+            assert_eq!(super::encode(&reading), message);
+        }
+
+        assert_eq!(
+            super::encode(&SingleReading::Humidity(-1.0)),
+            [0x41, 0x00, 0x00, 0x41, 0x0d]
+        );
+    }
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(super::celsius_to_ticks(super::ticks_to_celsius(4713)), 4713);
+        assert_eq!(super::celsius_to_ticks(-300.0), 0);
+        assert_eq!(super::celsius_to_ticks(5000.0), u16::MAX);
+
+        assert_eq!(
+            super::humidity_to_ticks(super::ticks_to_humidity(4567)),
+            4567
+        );
+        assert_eq!(super::humidity_to_ticks(-1.0), 0);
+        assert_eq!(super::humidity_to_ticks(1000.0), u16::MAX);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for value in 0..=u16::MAX {
+            let [hi, lo] = value.to_be_bytes();
+            for opcode in [b'A', b'B', b'P', 0x6d] {
+                let message = [
+                    opcode,
+                    hi,
+                    lo,
+                    opcode.wrapping_add(hi).wrapping_add(lo),
+                    0x0d,
+                ];
+                let reading = super::decode(message).unwrap();
+                assert_eq!(super::encode(&reading), message);
+            }
+        }
+
+        for i in -400..=1000 {
+            let temperature = i as f32 * 0.1;
+            let message = super::encode(&SingleReading::Temperature(temperature));
+            match super::decode(message) {
+                Ok(SingleReading::Temperature(val)) => {
+                    assert!((val - temperature).abs() <= 0.0625 / 2.0 + 1e-4)
+                }
+                _ => panic!(),
+            }
+
+            let humidity = (i + 400) as f32 * 0.07;
+            let message = super::encode(&SingleReading::Humidity(humidity));
+            match super::decode(message) {
+                Ok(SingleReading::Humidity(val)) => {
+                    assert!((val - humidity).abs() <= 0.005 + 1e-4)
+                }
+                _ => panic!(),
+            }
+        }
+    }
+
+    #[test]
+    fn test_corruption() {
+        let readings = [
+            SingleReading::CO2(1111),
+            SingleReading::Temperature(21.4),
+            SingleReading::Humidity(45.0),
+            SingleReading::Unknown(0x6d, 0x1234),
+        ];
+        for reading in &readings {
+            let message = super::encode(reading);
+            for i in 0..message.len() {
+                for mask in 1..=u8::MAX {
+                    let mut corrupted = message;
+                    corrupted[i] ^= mask;
+                    match super::decode(corrupted) {
+                        Err(Error::InvalidMessage) | Err(Error::Checksum) => {}
+                        _ => panic!("{:02x?} was not rejected", corrupted),
+                    }
+                }
+            }
+        }
+    }
 }